@@ -4,9 +4,10 @@
 use anyhow::Context;
 use nexlint::prelude::*;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 /// Allow certain characters in file paths.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct AllowedPaths {
     allowed_regex: Regex,
 }
@@ -14,6 +15,26 @@ pub struct AllowedPaths {
 // Regex for allowed characters in paths. Regex must have ^ and $ anchors.
 pub const DEFAULT_ALLOWED_PATHS_REGEX: &str = "^([a-zA-Z0-9._\\-/@:]|-)+$";
 
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AllowedPathsConfig {
+    /// Regex that every file path must match. Must have ^ and $ anchors.
+    #[serde(default = "default_allowed_paths_regex")]
+    pub regex: String,
+}
+
+impl Default for AllowedPathsConfig {
+    fn default() -> Self {
+        Self {
+            regex: default_allowed_paths_regex(),
+        }
+    }
+}
+
+fn default_allowed_paths_regex() -> String {
+    DEFAULT_ALLOWED_PATHS_REGEX.to_owned()
+}
+
 impl AllowedPaths {
     pub fn new(allowed_paths: &str) -> crate::Result<Self> {
         Ok(Self {
@@ -27,6 +48,18 @@ impl Linter for AllowedPaths {
     fn name(&self) -> &'static str {
         "allowed-paths"
     }
+
+    fn description(&self) -> &'static str {
+        "Requires file paths to match a regex of allowed characters"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/allowed-paths.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Layout
+    }
 }
 
 impl FilePathLinter for AllowedPaths {
@@ -0,0 +1,330 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Builds the set of built-in linters from a [`NexLintConfig`].
+
+use crate::{
+    allowed_paths::{AllowedPaths, AllowedPathsConfig, DEFAULT_ALLOWED_PATHS_REGEX},
+    guppy::*,
+    license::{LicenseHeader, LicenseHeaderConfig},
+    toml::RootToml,
+    whitespace::{build_exceptions, EofNewline, TrailingWhitespace, WhitespaceConfig},
+};
+use globset::GlobSet;
+use nexlint::prelude::*;
+use serde::Deserialize;
+
+/// The names of all built-in lints, along with whether they're enabled by default.
+///
+/// Lints that need project-specific configuration to be useful are disabled by default.
+pub const BUILTIN_LINTS: &[(&str, bool)] = &[
+    ("banned-deps", false),
+    ("direct-dep-dups", true),
+    ("direct-duplicate-git-dependencies", false),
+    ("crate-names-paths", true),
+    ("irrelevant-build-deps", true),
+    ("unpublished-packages-only-use-path-dependencies", true),
+    (
+        "published-packages-dont-depend-on-unpublished-packages",
+        true,
+    ),
+    ("only-publish-to-crates-io", true),
+    ("enforced-attributes", false),
+    ("crates-in-crates-directory", false),
+    ("crates-only-in-crates-directory", false),
+    ("allowed-paths", true),
+    ("license-header", false),
+    ("root-toml", true),
+    ("eof-newline", true),
+    ("trailing-whitespace", true),
+];
+
+/// Configuration for the built-in lints.
+///
+/// Each field is `None` if the corresponding lint is disabled. Configuration is only read for
+/// enabled lints.
+#[derive(Debug)]
+pub struct BuiltinLints {
+    banned_deps: Option<BannedDepsConfig>,
+    direct_dep_dups: Option<DirectDepDupsConfig>,
+    direct_duplicate_git_dependencies: Option<NoConfig>,
+    crate_names_paths: Option<NoConfig>,
+    irrelevant_build_deps: Option<NoConfig>,
+    unpublished_packages_only_use_path_dependencies: Option<NoConfig>,
+    published_packages_dont_depend_on_unpublished_packages: Option<NoConfig>,
+    only_publish_to_crates_io: Option<NoConfig>,
+    enforced_attributes: Option<EnforcedAttributesConfig>,
+    crates_in_crates_directory: Option<NoConfig>,
+    crates_only_in_crates_directory: Option<NoConfig>,
+    allowed_paths: Option<AllowedPaths>,
+    license_header: Option<LicenseHeaderConfig>,
+    root_toml: Option<NoConfig>,
+    eof_newline: Option<GlobSet>,
+    trailing_whitespace: Option<GlobSet>,
+}
+
+/// Configuration for lints that don't take any.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NoConfig {}
+
+impl BuiltinLints {
+    /// Reads configuration for the built-in lints.
+    ///
+    /// Returns an error if the configuration mentions an unknown lint, or if the configuration
+    /// for an enabled lint is invalid or has unknown keys.
+    pub fn from_config(config: &NexLintConfig) -> crate::Result<Self> {
+        config.check_lint_names(BUILTIN_LINTS.iter().map(|(name, _)| *name))?;
+
+        Ok(Self {
+            banned_deps: lint_config(config, "banned-deps")?,
+            direct_dep_dups: lint_config(config, "direct-dep-dups")?,
+            direct_duplicate_git_dependencies: lint_config(
+                config,
+                "direct-duplicate-git-dependencies",
+            )?,
+            crate_names_paths: lint_config(config, "crate-names-paths")?,
+            irrelevant_build_deps: lint_config(config, "irrelevant-build-deps")?,
+            unpublished_packages_only_use_path_dependencies: lint_config(
+                config,
+                "unpublished-packages-only-use-path-dependencies",
+            )?,
+            published_packages_dont_depend_on_unpublished_packages: lint_config(
+                config,
+                "published-packages-dont-depend-on-unpublished-packages",
+            )?,
+            only_publish_to_crates_io: lint_config(config, "only-publish-to-crates-io")?,
+            enforced_attributes: lint_config(config, "enforced-attributes")?,
+            crates_in_crates_directory: lint_config(config, "crates-in-crates-directory")?,
+            crates_only_in_crates_directory: lint_config(
+                config,
+                "crates-only-in-crates-directory",
+            )?,
+            allowed_paths: lint_config::<AllowedPathsConfig>(config, "allowed-paths")?
+                .map(|config| AllowedPaths::new(&config.regex))
+                .transpose()?,
+            license_header: lint_config(config, "license-header")?,
+            root_toml: lint_config(config, "root-toml")?,
+            eof_newline: lint_config::<WhitespaceConfig>(config, "eof-newline")?
+                .map(|config| build_exceptions(&config.exceptions))
+                .transpose()?,
+            trailing_whitespace: lint_config::<WhitespaceConfig>(config, "trailing-whitespace")?
+                .map(|config| build_exceptions(&config.exceptions))
+                .transpose()?,
+        })
+    }
+
+    /// Returns the enabled linters.
+    pub fn linters(&self) -> BuiltinLinters<'_> {
+        let mut linters = BuiltinLinters::default();
+
+        if let Some(config) = &self.banned_deps {
+            linters.project.push(Box::new(BannedDeps::new(config)));
+        }
+        if let Some(config) = &self.direct_dep_dups {
+            linters.project.push(Box::new(DirectDepDups::new(config)));
+        }
+        if self.direct_duplicate_git_dependencies.is_some() {
+            linters
+                .project
+                .push(Box::new(DirectDuplicateGitDependencies));
+        }
+
+        if self.crate_names_paths.is_some() {
+            linters.package.push(Box::new(CrateNamesPaths));
+        }
+        if self.irrelevant_build_deps.is_some() {
+            linters.package.push(Box::new(IrrelevantBuildDeps));
+        }
+        if self
+            .unpublished_packages_only_use_path_dependencies
+            .is_some()
+        {
+            linters
+                .package
+                .push(Box::new(UnpublishedPackagesOnlyUsePathDependencies::new()));
+        }
+        if self
+            .published_packages_dont_depend_on_unpublished_packages
+            .is_some()
+        {
+            linters
+                .package
+                .push(Box::new(PublishedPackagesDontDependOnUnpublishedPackages));
+        }
+        if self.only_publish_to_crates_io.is_some() {
+            linters.package.push(Box::new(OnlyPublishToCratesIo));
+        }
+        if let Some(config) = &self.enforced_attributes {
+            linters
+                .package
+                .push(Box::new(EnforcedAttributes::new(config)));
+        }
+        if self.crates_in_crates_directory.is_some() {
+            linters.package.push(Box::new(CratesInCratesDirectory));
+        }
+        if self.crates_only_in_crates_directory.is_some() {
+            linters.package.push(Box::new(CratesOnlyInCratesDirectory));
+        }
+
+        if let Some(allowed_paths) = &self.allowed_paths {
+            linters.file_path.push(Box::new(allowed_paths.clone()));
+        }
+
+        if let Some(config) = &self.license_header {
+            linters
+                .content
+                .push(Box::new(LicenseHeader::new(&config.header)));
+        }
+        if self.root_toml.is_some() {
+            linters.content.push(Box::new(RootToml));
+        }
+        if let Some(exceptions) = &self.eof_newline {
+            linters.content.push(Box::new(EofNewline::new(exceptions)));
+        }
+        if let Some(exceptions) = &self.trailing_whitespace {
+            linters
+                .content
+                .push(Box::new(TrailingWhitespace::new(exceptions)));
+        }
+
+        linters
+    }
+}
+
+fn lint_config<T: serde::de::DeserializeOwned>(
+    config: &NexLintConfig,
+    name: &'static str,
+) -> crate::Result<Option<T>> {
+    let enabled = config.is_enabled(name, enabled_by_default(name));
+    // Configured lints are parsed even if they're disabled, so that mistakes are caught before the
+    // lint is enabled.
+    if enabled || config.lint(name).is_some() {
+        let lint_config = config.lint_config(name)?;
+        Ok(enabled.then_some(lint_config))
+    } else {
+        Ok(None)
+    }
+}
+
+fn enabled_by_default(name: &str) -> bool {
+    BUILTIN_LINTS
+        .iter()
+        .find_map(|(builtin, enabled)| (*builtin == name).then_some(*enabled))
+        .expect("name is a built-in lint")
+}
+
+/// A built-in lint, along with its metadata.
+#[derive(Clone, Debug)]
+pub struct BuiltinLint {
+    info: LinterInfo,
+    enabled_by_default: bool,
+}
+
+impl BuiltinLint {
+    /// Returns the metadata for this lint.
+    pub fn info(&self) -> &LinterInfo {
+        &self.info
+    }
+
+    /// Returns true if this lint runs unless it's disabled in the configuration.
+    pub fn enabled_by_default(&self) -> bool {
+        self.enabled_by_default
+    }
+
+    /// Returns true if this lint is enabled in the given configuration.
+    pub fn is_enabled(&self, config: &NexLintConfig) -> bool {
+        config.is_enabled(self.info.name(), self.enabled_by_default)
+    }
+}
+
+/// Returns every built-in lint, whether or not it's enabled, in the order they're run.
+pub fn builtin_registry() -> Vec<BuiltinLint> {
+    // Metadata doesn't depend on configuration, so use the defaults.
+    let no_exceptions = GlobSet::empty();
+    let allowed_paths =
+        AllowedPaths::new(DEFAULT_ALLOWED_PATHS_REGEX).expect("default regex is valid");
+    let infos = [
+        LinterInfo::new(
+            &BannedDeps::new(&BannedDepsConfig::default()),
+            LinterKind::Project,
+        ),
+        LinterInfo::new(
+            &DirectDepDups::new(&DirectDepDupsConfig::default()),
+            LinterKind::Project,
+        ),
+        LinterInfo::new(&DirectDuplicateGitDependencies, LinterKind::Project),
+        LinterInfo::new(&CrateNamesPaths, LinterKind::Package),
+        LinterInfo::new(&IrrelevantBuildDeps, LinterKind::Package),
+        LinterInfo::new(
+            &UnpublishedPackagesOnlyUsePathDependencies::new(),
+            LinterKind::Package,
+        ),
+        LinterInfo::new(
+            &PublishedPackagesDontDependOnUnpublishedPackages,
+            LinterKind::Package,
+        ),
+        LinterInfo::new(&OnlyPublishToCratesIo, LinterKind::Package),
+        LinterInfo::new(
+            &EnforcedAttributes::new(&EnforcedAttributesConfig::default()),
+            LinterKind::Package,
+        ),
+        LinterInfo::new(&CratesInCratesDirectory, LinterKind::Package),
+        LinterInfo::new(&CratesOnlyInCratesDirectory, LinterKind::Package),
+        LinterInfo::new(&allowed_paths, LinterKind::FilePath),
+        LinterInfo::new(&LicenseHeader::new(""), LinterKind::Content),
+        LinterInfo::new(&RootToml, LinterKind::Content),
+        LinterInfo::new(&EofNewline::new(&no_exceptions), LinterKind::Content),
+        LinterInfo::new(
+            &TrailingWhitespace::new(&no_exceptions),
+            LinterKind::Content,
+        ),
+    ];
+
+    infos
+        .into_iter()
+        .map(|info| BuiltinLint {
+            enabled_by_default: enabled_by_default(info.name()),
+            info,
+        })
+        .collect()
+}
+
+/// The enabled built-in linters, grouped by phase.
+#[derive(Default)]
+pub struct BuiltinLinters<'cfg> {
+    project: Vec<Box<dyn ProjectLinter + 'cfg>>,
+    package: Vec<Box<dyn PackageLinter + 'cfg>>,
+    file_path: Vec<Box<dyn FilePathLinter + 'cfg>>,
+    content: Vec<Box<dyn ContentLinter + 'cfg>>,
+}
+
+impl<'cfg> BuiltinLinters<'cfg> {
+    pub fn project_linters(&self) -> Vec<&dyn ProjectLinter> {
+        self.project
+            .iter()
+            .map(|linter| linter.as_ref() as _)
+            .collect()
+    }
+
+    pub fn package_linters(&self) -> Vec<&dyn PackageLinter> {
+        self.package
+            .iter()
+            .map(|linter| linter.as_ref() as _)
+            .collect()
+    }
+
+    pub fn file_path_linters(&self) -> Vec<&dyn FilePathLinter> {
+        self.file_path
+            .iter()
+            .map(|linter| linter.as_ref() as _)
+            .collect()
+    }
+
+    pub fn content_linters(&self) -> Vec<&dyn ContentLinter> {
+        self.content
+            .iter()
+            .map(|linter| linter.as_ref() as _)
+            .collect()
+    }
+}
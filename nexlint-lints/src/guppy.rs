@@ -14,12 +14,12 @@ pub struct BannedDeps<'cfg> {
     config: &'cfg BannedDepsConfig,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Clone, Default, Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub struct BannedDepsConfig(pub HashMap<String, BannedDepConfig>);
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "kebab-case")]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct BannedDepConfig {
     /// Message to print if this dependency is found
     pub message: String,
@@ -46,6 +46,18 @@ impl Linter for BannedDeps<'_> {
     fn name(&self) -> &'static str {
         "banned-deps"
     }
+
+    fn description(&self) -> &'static str {
+        "Bans crates from the dependency graph"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/banned-deps.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Dependencies
+    }
 }
 
 impl<'cfg> ProjectLinter for BannedDeps<'cfg> {
@@ -108,6 +120,11 @@ impl<'cfg> ProjectLinter for BannedDeps<'cfg> {
 
         Ok(RunStatus::Executed)
     }
+
+    fn affected_only(&self) -> bool {
+        // This linter only depends on the package graph.
+        true
+    }
 }
 
 /// Enforce attributes on workspace crates.
@@ -116,8 +133,8 @@ pub struct EnforcedAttributes<'cfg> {
     config: &'cfg EnforcedAttributesConfig,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "kebab-case")]
+#[derive(Clone, Default, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct EnforcedAttributesConfig {
     /// Ensure the authors of every workspace crate are set to this.
     pub authors: Option<Vec<String>>,
@@ -135,6 +152,18 @@ impl Linter for EnforcedAttributes<'_> {
     fn name(&self) -> &'static str {
         "enforced-attributes"
     }
+
+    fn description(&self) -> &'static str {
+        "Requires workspace packages to set the configured authors and license"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/enforced-attributes.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Metadata
+    }
 }
 
 impl PackageLinter for EnforcedAttributes<'_> {
@@ -173,6 +202,18 @@ impl Linter for CrateNamesPaths {
     fn name(&self) -> &'static str {
         "crate-names-paths"
     }
+
+    fn description(&self) -> &'static str {
+        "Requires crate names, paths and build targets to use '-' rather than '_'"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/crate-names-paths.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Layout
+    }
 }
 
 impl PackageLinter for CrateNamesPaths {
@@ -229,6 +270,18 @@ impl Linter for IrrelevantBuildDeps {
     fn name(&self) -> &'static str {
         "irrelevant-build-deps"
     }
+
+    fn description(&self) -> &'static str {
+        "Reports build dependencies in packages without a build script"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/irrelevant-build-deps.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Dependencies
+    }
 }
 
 impl PackageLinter for IrrelevantBuildDeps {
@@ -257,9 +310,11 @@ pub struct DirectDepDups<'cfg> {
     config: &'cfg DirectDepDupsConfig,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "kebab-case")]
+#[derive(Clone, Default, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct DirectDepDupsConfig {
+    /// Third-party crates that packages may depend on multiple versions of.
+    #[serde(default)]
     pub allow: Vec<String>,
 }
 
@@ -273,6 +328,18 @@ impl Linter for DirectDepDups<'_> {
     fn name(&self) -> &'static str {
         "direct-dep-dups"
     }
+
+    fn description(&self) -> &'static str {
+        "Reports third-party crates that workspace packages depend on at several versions"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/direct-dep-dups.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Dependencies
+    }
 }
 
 impl ProjectLinter for DirectDepDups<'_> {
@@ -325,6 +392,11 @@ impl ProjectLinter for DirectDepDups<'_> {
 
         Ok(RunStatus::Executed)
     }
+
+    fn affected_only(&self) -> bool {
+        // This linter only depends on the package graph.
+        true
+    }
 }
 
 #[derive(Debug)]
@@ -334,6 +406,18 @@ impl Linter for DirectDuplicateGitDependencies {
     fn name(&self) -> &'static str {
         "direct-duplicate-git-dependencies"
     }
+
+    fn description(&self) -> &'static str {
+        "Reports Git dependencies on one repository at several revisions"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/direct-duplicate-git-dependencies.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Dependencies
+    }
 }
 
 impl ProjectLinter for DirectDuplicateGitDependencies {
@@ -396,6 +480,11 @@ impl ProjectLinter for DirectDuplicateGitDependencies {
 
         Ok(RunStatus::Executed)
     }
+
+    fn affected_only(&self) -> bool {
+        // This linter only depends on the package graph.
+        true
+    }
 }
 
 /// Ensure that all unpublished packages only use path dependencies for workspace dependencies
@@ -422,6 +511,18 @@ impl Linter for UnpublishedPackagesOnlyUsePathDependencies {
     fn name(&self) -> &'static str {
         "unpublished-packages-only-use-path-dependencies"
     }
+
+    fn description(&self) -> &'static str {
+        "Requires unpublished packages to depend on workspace packages by path only"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/unpublished-packages-only-use-path-dependencies.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Publishing
+    }
 }
 
 impl PackageLinter for UnpublishedPackagesOnlyUsePathDependencies {
@@ -461,6 +562,18 @@ impl Linter for PublishedPackagesDontDependOnUnpublishedPackages {
     fn name(&self) -> &'static str {
         "published-packages-dont-depend-on-unpublished-packages"
     }
+
+    fn description(&self) -> &'static str {
+        "Requires published packages to only depend on other published packages"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/published-packages-dont-depend-on-unpublished-packages.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Publishing
+    }
 }
 
 impl PackageLinter for PublishedPackagesDontDependOnUnpublishedPackages {
@@ -501,6 +614,18 @@ impl Linter for OnlyPublishToCratesIo {
     fn name(&self) -> &'static str {
         "only-publish-to-crates-io"
     }
+
+    fn description(&self) -> &'static str {
+        "Requires publishable packages to be restricted to crates.io"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/only-publish-to-crates-io.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Publishing
+    }
 }
 
 impl PackageLinter for OnlyPublishToCratesIo {
@@ -543,6 +668,18 @@ impl Linter for CratesInCratesDirectory {
     fn name(&self) -> &'static str {
         "crates-in-crates-directory"
     }
+
+    fn description(&self) -> &'static str {
+        "Requires packages in crates/ to be in a directory named after the crate"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/crates-in-crates-directory.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Layout
+    }
 }
 
 impl PackageLinter for CratesInCratesDirectory {
@@ -586,6 +723,18 @@ impl Linter for CratesOnlyInCratesDirectory {
     fn name(&self) -> &'static str {
         "crates-only-in-crates-directory"
     }
+
+    fn description(&self) -> &'static str {
+        "Requires every workspace package to be in crates/"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/crates-only-in-crates-directory.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Layout
+    }
 }
 
 impl PackageLinter for CratesOnlyInCratesDirectory {
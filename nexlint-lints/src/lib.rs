@@ -2,11 +2,30 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use anyhow::anyhow;
-use nexlint::prelude::LintResults;
+use nexlint::{
+    prelude::{LintLevel, LintResults, Reporter},
+    report::{
+        checkstyle::CheckstyleReporter,
+        github::{self, GithubReporter},
+        gitlab::GitlabReporter,
+        human::{self, HumanReporter},
+        json::{JsonLinesReporter, JsonReporter},
+        junit::JunitReporter,
+        sarif::SarifReporter,
+    },
+};
+use std::{
+    collections::BTreeMap,
+    env, fmt,
+    io::{self, IsTerminal, Write},
+    str::FromStr,
+};
 
 pub use anyhow::Result;
+pub use builtin::{builtin_registry, BuiltinLint, BuiltinLinters, BuiltinLints, BUILTIN_LINTS};
 
 mod allowed_paths;
+mod builtin;
 mod guppy;
 mod license;
 mod toml;
@@ -22,40 +41,465 @@ pub mod project {
 pub mod package {
     pub use super::guppy::{
         CrateNamesPaths, CratesInCratesDirectory, CratesOnlyInCratesDirectory, EnforcedAttributes,
-        IrrelevantBuildDeps, OnlyPublishToCratesIo,
+        EnforcedAttributesConfig, IrrelevantBuildDeps, OnlyPublishToCratesIo,
         PublishedPackagesDontDependOnUnpublishedPackages,
         UnpublishedPackagesOnlyUsePathDependencies,
     };
 }
 
 pub mod file_path {
-    pub use super::allowed_paths::{AllowedPaths, DEFAULT_ALLOWED_PATHS_REGEX};
+    pub use super::allowed_paths::{AllowedPaths, AllowedPathsConfig, DEFAULT_ALLOWED_PATHS_REGEX};
 }
 
 pub mod content {
     pub use super::{
-        license::LicenseHeader,
+        license::{LicenseHeader, LicenseHeaderConfig},
         toml::RootToml,
-        whitespace::{build_exceptions, EofNewline, TrailingWhitespace},
+        whitespace::{build_exceptions, EofNewline, TrailingWhitespace, WhitespaceConfig},
     };
 }
 
-pub fn handle_lint_results(results: LintResults) -> crate::Result<()> {
-    // TODO: handle skipped results
+/// The format lint results are written out in.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum MessageFormat {
+    /// Human-readable output.
+    #[default]
+    Human,
+    /// A single JSON document containing all results.
+    Json,
+    /// One JSON object per line for each message or skipped lint.
+    JsonLines,
+    /// A SARIF 2.1.0 log, for uploading to code scanning services.
+    Sarif,
+    /// GitHub Actions workflow commands, which show up as annotations on pull requests.
+    Github,
+    /// A JUnit XML report, with a test suite per linter and a test case per target.
+    Junit,
+    /// A Checkstyle XML report.
+    Checkstyle,
+    /// A GitLab Code Quality report.
+    Gitlab,
+}
+
+impl MessageFormat {
+    /// The names accepted by `FromStr`.
+    pub const VARIANTS: &'static [&'static str] = &[
+        "human",
+        "json",
+        "json-lines",
+        "sarif",
+        "github",
+        "junit",
+        "checkstyle",
+        "gitlab",
+    ];
+
+    /// Returns the format to use if none was specified: `Github` when running in GitHub Actions,
+    /// and `Human` otherwise.
+    pub fn from_env() -> Self {
+        if github::is_github_actions() {
+            MessageFormat::Github
+        } else {
+            MessageFormat::Human
+        }
+    }
+}
+
+impl FromStr for MessageFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "human" => Ok(MessageFormat::Human),
+            "json" => Ok(MessageFormat::Json),
+            "json-lines" => Ok(MessageFormat::JsonLines),
+            "sarif" => Ok(MessageFormat::Sarif),
+            "github" => Ok(MessageFormat::Github),
+            "junit" => Ok(MessageFormat::Junit),
+            "checkstyle" => Ok(MessageFormat::Checkstyle),
+            "gitlab" => Ok(MessageFormat::Gitlab),
+            other => Err(anyhow!(
+                "unknown message format '{}' (expected one of: {})",
+                other,
+                Self::VARIANTS.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MessageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MessageFormat::Human => write!(f, "human"),
+            MessageFormat::Json => write!(f, "json"),
+            MessageFormat::JsonLines => write!(f, "json-lines"),
+            MessageFormat::Sarif => write!(f, "sarif"),
+            MessageFormat::Github => write!(f, "github"),
+            MessageFormat::Junit => write!(f, "junit"),
+            MessageFormat::Checkstyle => write!(f, "checkstyle"),
+            MessageFormat::Gitlab => write!(f, "gitlab"),
+        }
+    }
+}
+
+/// The lowest message level that causes `handle_lint_results` to fail.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum FailOn {
+    /// Fail if there are any errors.
+    #[default]
+    Error,
+    /// Fail if there are any errors or warnings.
+    Warning,
+    /// Never fail because of lint messages.
+    Never,
+}
+
+impl FailOn {
+    /// The names accepted by `FromStr`.
+    pub const VARIANTS: &'static [&'static str] = &["error", "warning", "never"];
+
+    /// Returns the lowest message level that causes a failure, or `None` if nothing does.
+    pub fn level(self) -> Option<LintLevel> {
+        match self {
+            FailOn::Error => Some(LintLevel::Error),
+            FailOn::Warning => Some(LintLevel::Warning),
+            FailOn::Never => None,
+        }
+    }
+}
+
+impl FromStr for FailOn {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "error" => Ok(FailOn::Error),
+            "warning" => Ok(FailOn::Warning),
+            "never" => Ok(FailOn::Never),
+            other => Err(anyhow!(
+                "unknown failure threshold '{}' (expected one of: {})",
+                other,
+                Self::VARIANTS.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FailOn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FailOn::Error => write!(f, "error"),
+            FailOn::Warning => write!(f, "warning"),
+            FailOn::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// How much detail to show about skipped lints in human-readable output.
+///
+/// Machine-readable formats always include every skipped lint.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ShowSkipped {
+    /// Don't show skipped lints.
+    #[default]
+    Never,
+    /// Show counts of skipped lints, grouped by linter and reason.
+    Summary,
+    /// Show the summary, followed by every skipped lint.
+    Full,
+}
+
+impl ShowSkipped {
+    /// The names accepted by `FromStr`.
+    pub const VARIANTS: &'static [&'static str] = &["never", "summary", "full"];
+}
+
+impl FromStr for ShowSkipped {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "never" => Ok(ShowSkipped::Never),
+            "summary" => Ok(ShowSkipped::Summary),
+            "full" => Ok(ShowSkipped::Full),
+            other => Err(anyhow!(
+                "unknown skipped lint mode '{}' (expected one of: {})",
+                other,
+                Self::VARIANTS.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ShowSkipped {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShowSkipped::Never => write!(f, "never"),
+            ShowSkipped::Summary => write!(f, "summary"),
+            ShowSkipped::Full => write!(f, "full"),
+        }
+    }
+}
+
+/// How to output the timings recorded during a lint run.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum TimingsFormat {
+    /// A table of linters from slowest to fastest, followed by phases and file loading.
+    #[default]
+    Table,
+    /// A JSON trace that can be loaded into `chrome://tracing` or Perfetto.
+    ChromeTrace,
+}
+
+impl TimingsFormat {
+    /// The names accepted by `FromStr`.
+    pub const VARIANTS: &'static [&'static str] = &["table", "chrome-trace"];
+}
+
+impl FromStr for TimingsFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "table" => Ok(TimingsFormat::Table),
+            "chrome-trace" => Ok(TimingsFormat::ChromeTrace),
+            other => Err(anyhow!(
+                "unknown timings format '{}' (expected one of: {})",
+                other,
+                Self::VARIANTS.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TimingsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimingsFormat::Table => write!(f, "table"),
+            TimingsFormat::ChromeTrace => write!(f, "chrome-trace"),
+        }
+    }
+}
+
+/// Whether to use colors in human-readable output.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ColorMode {
+    /// Use colors if standard output is a terminal.
+    #[default]
+    Auto,
+    /// Always use colors.
+    Always,
+    /// Never use colors.
+    Never,
+}
+
+impl ColorMode {
+    /// The names accepted by `FromStr`.
+    pub const VARIANTS: &'static [&'static str] = &["auto", "always", "never"];
+
+    /// Returns true if standard output should be colored. In auto mode, colors are used if
+    /// standard output is a terminal, unless `NO_COLOR` is set or `TERM` is `dumb`.
+    fn use_color(self) -> bool {
+        match self {
+            ColorMode::Auto => {
+                io::stdout().is_terminal()
+                    && env::var_os("NO_COLOR").is_none()
+                    && env::var("TERM").map_or(true, |term| term != "dumb")
+            }
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            other => Err(anyhow!(
+                "unknown color mode '{}' (expected one of: {})",
+                other,
+                Self::VARIANTS.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ColorMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorMode::Auto => write!(f, "auto"),
+            ColorMode::Always => write!(f, "always"),
+            ColorMode::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// Options controlling how lint results are reported.
+#[derive(Clone, Debug, Default)]
+pub struct ReportOptions {
+    pub message_format: MessageFormat,
+    pub fail_on: FailOn,
+    pub show_skipped: ShowSkipped,
+    pub color: ColorMode,
+    /// The width to wrap human-readable output to. Defaults to the `COLUMNS` environment variable
+    /// if set, and 100 columns otherwise.
+    pub width: Option<usize>,
+}
+
+impl ReportOptions {
+    /// Returns the reporter for the selected message format.
+    pub fn reporter(&self) -> Box<dyn Reporter> {
+        match self.message_format {
+            MessageFormat::Human => Box::new(HumanWithSkipped {
+                human: HumanReporter::new()
+                    .with_color(self.color.use_color())
+                    .with_width(self.width()),
+                show_skipped: self.show_skipped,
+            }),
+            MessageFormat::Json => Box::new(JsonReporter),
+            MessageFormat::JsonLines => Box::new(JsonLinesReporter),
+            MessageFormat::Sarif => Box::new(SarifReporter),
+            MessageFormat::Github => Box::new(GithubReporter),
+            MessageFormat::Junit => {
+                Box::new(JunitReporter::new().with_fail_level(self.fail_on.level()))
+            }
+            MessageFormat::Checkstyle => Box::new(CheckstyleReporter),
+            MessageFormat::Gitlab => Box::new(GitlabReporter),
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.width
+            .or_else(|| env::var("COLUMNS").ok()?.parse().ok())
+            .unwrap_or(human::DEFAULT_WIDTH)
+    }
+}
+
+/// The error returned by `handle_lint_results` if lint messages reached the failure threshold.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LintFailure {
+    /// There were lint errors.
+    Errors(usize),
+    /// There were no lint errors, but there were warnings.
+    Warnings(usize),
+}
+
+impl LintFailure {
+    /// Returns the process exit code to use for this failure: 1 for errors and 2 for warnings.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            LintFailure::Errors(_) => 1,
+            LintFailure::Warnings(_) => 2,
+        }
+    }
+}
+
+impl fmt::Display for LintFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LintFailure::Errors(count) => write!(f, "there were {} lint errors", count),
+            LintFailure::Warnings(count) => write!(f, "there were {} lint warnings", count),
+        }
+    }
+}
+
+impl std::error::Error for LintFailure {}
+
+/// Writes out lint results, then returns a [`LintFailure`] if any messages reached the failure
+/// threshold.
+pub fn handle_lint_results(results: LintResults, options: &ReportOptions) -> crate::Result<()> {
+    report_lint_results(results, options.reporter(), options.fail_on)
+}
+
+/// Writes out lint results to standard output with a custom reporter, then returns a
+/// [`LintFailure`] if any messages reached the failure threshold.
+pub fn report_lint_results(
+    results: LintResults,
+    reporter: impl Reporter,
+    fail_on: FailOn,
+) -> crate::Result<()> {
+    reporter.report(&results, &mut io::stdout().lock())?;
+
+    let count = |level| {
+        results
+            .messages
+            .iter()
+            .filter(|(_, message)| message.level() == level)
+            .count()
+    };
+    let errors = count(LintLevel::Error);
+    let warnings = count(LintLevel::Warning);
+
+    match fail_on {
+        FailOn::Error | FailOn::Warning if errors > 0 => Err(LintFailure::Errors(errors).into()),
+        FailOn::Warning if warnings > 0 => Err(LintFailure::Warnings(warnings).into()),
+        _ => Ok(()),
+    }
+}
+
+/// Human-readable output, followed by skipped lints if requested.
+struct HumanWithSkipped {
+    human: HumanReporter,
+    show_skipped: ShowSkipped,
+}
+
+impl Reporter for HumanWithSkipped {
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> nexlint::Result<()> {
+        self.human.report(results, writer)?;
+        write_skipped(results, self.show_skipped, writer)
+            .map_err(|err| nexlint::SystemError::io("writing skipped lints", err))
+    }
+}
+
+fn write_skipped(
+    results: &LintResults,
+    show_skipped: ShowSkipped,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    if show_skipped == ShowSkipped::Never {
+        return Ok(());
+    }
+
+    let mut counts: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for (source, reason) in &results.skipped {
+        *counts.entry((source.name(), reason.name())).or_default() += 1;
+    }
 
-    for (source, message) in &results.messages {
-        println!(
-            "[{}] [{}] [{}]: {}\n",
-            message.level(),
-            source.name(),
-            source.kind(),
-            message.message()
-        );
+    writeln!(writer, "skipped lints ({} total):", results.skipped.len())?;
+    let name_width = counts.keys().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let reason_width = counts
+        .keys()
+        .map(|(_, reason)| reason.len())
+        .max()
+        .unwrap_or(0);
+    for ((name, reason), count) in &counts {
+        writeln!(
+            writer,
+            "  {:name_width$}  {:reason_width$}  {:>5}",
+            name,
+            reason,
+            count,
+            name_width = name_width,
+            reason_width = reason_width,
+        )?;
     }
 
-    if !results.messages.is_empty() {
-        Err(anyhow!("there were lint errors"))
-    } else {
-        Ok(())
+    if show_skipped == ShowSkipped::Full {
+        writeln!(writer)?;
+        for (source, reason) in &results.skipped {
+            writeln!(
+                writer,
+                "[SKIPPED] [{}] [{}]: {}",
+                source.name(),
+                source.kind(),
+                reason
+            )?;
+        }
     }
+    writeln!(writer)
 }
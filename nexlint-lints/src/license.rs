@@ -2,11 +2,19 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use nexlint::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 #[derive(Copy, Clone, Debug)]
 pub struct LicenseHeader<'a>(&'a str);
 
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LicenseHeaderConfig {
+    /// The license header every source file must start with, without comment markers.
+    pub header: String,
+}
+
 impl<'a> LicenseHeader<'a> {
     pub fn new(header: &'a str) -> Self {
         Self(header)
@@ -17,20 +25,39 @@ impl Linter for LicenseHeader<'_> {
     fn name(&self) -> &'static str {
         "license-header"
     }
+
+    fn description(&self) -> &'static str {
+        "Requires source files to start with a license header"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/license-header.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Style
+    }
 }
 
 impl ContentLinter for LicenseHeader<'_> {
-    fn pre_run<'l>(&self, file_ctx: &FilePathContext<'l>) -> Result<RunStatus<'l>> {
-        // TODO: Add a way to pass around state between pre_run and run, so that this computation
-        // only needs to be done once.
-        match FileType::new(file_ctx) {
-            Some(_) => Ok(RunStatus::Executed),
-            None => Ok(RunStatus::Skipped(SkipReason::UnsupportedExtension(
+    fn pre_run<'l>(&self, file_ctx: &FilePathContext<'l>) -> Result<PreRunStatus<'l>> {
+        let class = file_ctx.classify()?;
+        if let Some(reason) = class.exemption() {
+            return Ok(PreRunStatus::Skipped(reason));
+        }
+        match class.language().and_then(CommentStyle::new) {
+            Some(style) => Ok(PreRunStatus::with_state(style)),
+            None => Ok(PreRunStatus::Skipped(SkipReason::UnsupportedExtension(
                 file_ctx.extension(),
             ))),
         }
     }
 
+    fn cache_fingerprint(&self, ctx: &ContentContext<'_>) -> Option<String> {
+        let style = ctx.pre_run_state::<CommentStyle>().ok()?;
+        Some(format!("{:?}\0{}", style, self.0))
+    }
+
     fn run<'l>(
         &self,
         ctx: &ContentContext<'l>,
@@ -44,76 +71,90 @@ impl ContentLinter for LicenseHeader<'_> {
             }
         };
 
-        let file_type = FileType::new(ctx.file_ctx()).expect("None filtered out in pre_run");
-        // Determine if the file is missing the license header
-        let missing_header = match file_type {
-            FileType::Rust
-            | FileType::Proto
-            | FileType::JavaScript
-            | FileType::TypeScript
-            | FileType::Move => {
-                let maybe_license: HashSet<_> = content
-                    .lines()
-                    .skip_while(|line| line.is_empty())
-                    .take(4)
-                    .map(|s| s.trim_start_matches("// "))
-                    .collect();
-                !self
-                    .0
-                    .lines()
-                    .collect::<HashSet<_>>()
-                    .is_subset(&maybe_license)
-            }
-            FileType::Shell | FileType::Python => {
-                let maybe_license = content
-                    .lines()
-                    .skip_while(|line| line.starts_with("#!"))
-                    .skip_while(|line| line.is_empty())
-                    .take(4)
-                    .map(|s| s.trim_start_matches("# "))
-                    .collect();
-                !self
-                    .0
-                    .lines()
-                    .collect::<HashSet<_>>()
-                    .is_subset(&maybe_license)
+        let style = ctx.pre_run_state::<CommentStyle>()?;
+        let (comment_prefix, header_start) = match style {
+            CommentStyle::DoubleSlash => ("// ", 0),
+            CommentStyle::Hash => {
+                // The header goes after any shebang lines.
+                let shebang_len = content
+                    .split_inclusive('\n')
+                    .take_while(|line| line.starts_with("#!"))
+                    .map(str::len)
+                    .sum();
+                ("# ", shebang_len)
             }
         };
 
-        if missing_header {
-            out.write(LintLevel::Error, "missing license header");
+        // Determine if the file is missing any lines of the license header
+        let maybe_license: HashSet<_> = content[header_start..]
+            .lines()
+            .skip_while(|line| line.is_empty())
+            .take(4)
+            .map(|s| s.trim_start_matches(comment_prefix))
+            .collect();
+        let missing_lines: Vec<_> = self
+            .0
+            .lines()
+            .filter(|line| !maybe_license.contains(line))
+            .collect();
+
+        if !missing_lines.is_empty() {
+            // Point at the first line of the file, which is where the header is expected.
+            let first_line_len = content.lines().next().map_or(0, str::len);
+            let span = LintSpan::from_byte_range(content, 0..first_line_len);
+
+            let mut header = String::new();
+            for line in missing_lines {
+                header.push_str(format!("{}{}", comment_prefix, line).trim_end());
+                header.push('\n');
+            }
+            // Separate the header from the rest of the file, unless it continues an existing
+            // comment block.
+            let rest = &content[header_start..];
+            if !rest.is_empty()
+                && !rest.starts_with('\n')
+                && !rest.starts_with(comment_prefix.trim_end())
+            {
+                header.push('\n');
+            }
+            let fix = LintFix::new(
+                "add license header",
+                vec![TextEdit::insert(
+                    ctx.file_ctx().file_path(),
+                    header_start,
+                    header,
+                )],
+            );
+
+            out.write_message(
+                LintMessage::new(LintLevel::Error, "missing license header")
+                    .with_span(span)
+                    .with_fix(fix),
+            );
         }
 
         Ok(RunStatus::Executed)
     }
 }
 
-enum FileType {
-    Rust,
-    Shell,
-    Proto,
-    JavaScript,
-    TypeScript,
-    Move,
-    Python,
+/// The style of line comments the license header is written in.
+#[derive(Copy, Clone, Debug)]
+enum CommentStyle {
+    /// `// `, at the start of the file.
+    DoubleSlash,
+    /// `# `, after any `#!` lines.
+    Hash,
 }
 
-impl FileType {
-    fn new(ctx: &FilePathContext<'_>) -> Option<Self> {
-        match ctx.extension() {
-            Some("rs") => Some(FileType::Rust),
-            Some("sh") => Some(FileType::Shell),
-            Some("proto") => Some(FileType::Proto),
-            Some("js") => Some(FileType::JavaScript),
-            Some("jsx") => Some(FileType::JavaScript),
-            Some("cjs") => Some(FileType::JavaScript),
-            Some("mjs") => Some(FileType::JavaScript),
-            Some("ts") => Some(FileType::TypeScript),
-            Some("tsx") => Some(FileType::TypeScript),
-            Some("mts") => Some(FileType::TypeScript),
-            Some("cts") => Some(FileType::TypeScript),
-            Some("move") => Some(FileType::Move),
-            Some("py") => Some(FileType::Python),
+impl CommentStyle {
+    fn new(language: Language) -> Option<Self> {
+        match language {
+            Language::Rust
+            | Language::Proto
+            | Language::JavaScript
+            | Language::TypeScript
+            | Language::Move => Some(CommentStyle::DoubleSlash),
+            Language::Shell | Language::Python => Some(CommentStyle::Hash),
             _ => None,
         }
     }
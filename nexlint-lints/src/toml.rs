@@ -4,6 +4,7 @@
 use camino::Utf8Path;
 use nexlint::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 use toml::{de, ser};
 
 /// Checks on the root toml.
@@ -14,15 +15,29 @@ impl Linter for RootToml {
     fn name(&self) -> &'static str {
         "root-toml"
     }
+
+    fn description(&self) -> &'static str {
+        "Requires the root Cargo.toml to list every workspace member in order"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/root-toml.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Layout
+    }
 }
 
 impl ContentLinter for RootToml {
-    fn pre_run<'l>(&self, file_ctx: &FilePathContext<'l>) -> Result<RunStatus<'l>> {
+    fn pre_run<'l>(&self, file_ctx: &FilePathContext<'l>) -> Result<PreRunStatus<'l>> {
         let file_path = file_ctx.file_path();
         if file_path == "Cargo.toml" {
-            Ok(RunStatus::Executed)
+            Ok(RunStatus::Executed.into())
         } else {
-            Ok(RunStatus::Skipped(SkipReason::UnsupportedFile(file_path)))
+            Ok(PreRunStatus::Skipped(SkipReason::UnsupportedFile(
+                file_path,
+            )))
         }
     }
 
@@ -50,7 +65,7 @@ impl ContentLinter for RootToml {
         };
 
         if workspace.members != expected.members {
-            out.write(
+            let mut message = LintMessage::new(
                 LintLevel::Error,
                 toml_mismatch_message(
                     &expected,
@@ -58,10 +73,26 @@ impl ContentLinter for RootToml {
                     "workspace member list not canonical",
                 )?,
             );
+            let content = ctx.content().unwrap_or_default();
+            if let Some(range) = workspace_members_range(content) {
+                let canonical = to_toml_string(&expected).map_err(|err| {
+                    SystemError::ser("serializing expected workspace members", err)
+                })?;
+                let fix = LintFix::new(
+                    "sort and complete workspace member list",
+                    vec![TextEdit::replace(
+                        ctx.file_ctx().file_path(),
+                        range.clone(),
+                        canonical.trim_end(),
+                    )],
+                );
+                message = message
+                    .with_span(LintSpan::from_byte_range(content, range))
+                    .with_fix(fix);
+            }
+            out.write_message(message);
         }
 
-        // TODO: autofix support would be really nice!
-
         Ok(RunStatus::Executed)
     }
 }
@@ -81,6 +112,34 @@ pub(super) fn toml_mismatch_message<T: Serialize>(
     Ok(format!("{}:\n\n{}", header, diff))
 }
 
+/// Returns the byte range of the `members = [...]` array in the `[workspace]` table.
+///
+/// This is a best-effort textual search, since the TOML parser doesn't track spans. Returns `None`
+/// if the array couldn't be found.
+fn workspace_members_range(content: &str) -> Option<Range<usize>> {
+    let mut in_workspace = false;
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_workspace = trimmed == "[workspace]";
+        } else if in_workspace {
+            let is_members = trimmed
+                .strip_prefix("members")
+                .is_some_and(|rest| rest.trim_start().starts_with('='));
+            if is_members {
+                let key_start = line_start + (line.len() - line.trim_start().len());
+                let array_start = key_start + content[key_start..].find('[')?;
+                let array_end = array_start + content[array_start..].find(']')? + 1;
+                return Some(key_start..array_end);
+            }
+        }
+        line_start += line.len();
+    }
+
+    None
+}
+
 /// Serializes some data to toml using this project's standard code style.
 fn to_toml_string<T: Serialize>(data: &T) -> Result<String, ser::Error> {
     let mut dst = String::with_capacity(128);
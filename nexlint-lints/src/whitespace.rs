@@ -4,6 +4,15 @@
 use anyhow::Context;
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use nexlint::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct WhitespaceConfig {
+    /// Globs for files that are exempt from this check.
+    #[serde(default)]
+    pub exceptions: Vec<String>,
+}
 
 #[derive(Clone, Copy, Debug)]
 pub struct EofNewline<'cfg> {
@@ -20,11 +29,28 @@ impl Linter for EofNewline<'_> {
     fn name(&self) -> &'static str {
         "eof-newline"
     }
+
+    fn description(&self) -> &'static str {
+        "Requires text files to end with a newline"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/eof-newline.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Style
+    }
 }
 
 impl ContentLinter for EofNewline<'_> {
-    fn pre_run<'l>(&self, file_ctx: &FilePathContext<'l>) -> Result<RunStatus<'l>> {
-        Ok(skip_whitespace_checks(self.exceptions, file_ctx))
+    fn pre_run<'l>(&self, file_ctx: &FilePathContext<'l>) -> Result<PreRunStatus<'l>> {
+        Ok(skip_whitespace_checks(self.exceptions, file_ctx)?.into())
+    }
+
+    fn cache_fingerprint(&self, _ctx: &ContentContext<'_>) -> Option<String> {
+        // Exceptions only affect pre_run, so there's nothing else to cover.
+        Some(String::new())
     }
 
     fn run<'l>(
@@ -37,7 +63,20 @@ impl ContentLinter for EofNewline<'_> {
             None => return Ok(RunStatus::Skipped(SkipReason::NonUtf8Content)),
         };
         if !content.is_empty() && !content.ends_with('\n') {
-            out.write(LintLevel::Error, "missing newline at EOF");
+            let span = LintSpan::from_byte_range(content, content.len()..content.len());
+            let fix = LintFix::new(
+                "add newline at EOF",
+                vec![TextEdit::insert(
+                    ctx.file_ctx().file_path(),
+                    content.len(),
+                    "\n",
+                )],
+            );
+            out.write_message(
+                LintMessage::new(LintLevel::Error, "missing newline at EOF")
+                    .with_span(span)
+                    .with_fix(fix),
+            );
         }
         Ok(RunStatus::Executed)
     }
@@ -58,11 +97,28 @@ impl Linter for TrailingWhitespace<'_> {
     fn name(&self) -> &'static str {
         "trailing-whitespace"
     }
+
+    fn description(&self) -> &'static str {
+        "Reports whitespace at the ends of lines and blank lines at the end of files"
+    }
+
+    fn explanation(&self) -> &'static str {
+        include_str!("explanations/trailing-whitespace.md")
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Style
+    }
 }
 
 impl ContentLinter for TrailingWhitespace<'_> {
-    fn pre_run<'l>(&self, file_ctx: &FilePathContext<'l>) -> Result<RunStatus<'l>> {
-        Ok(skip_whitespace_checks(self.exceptions, file_ctx))
+    fn pre_run<'l>(&self, file_ctx: &FilePathContext<'l>) -> Result<PreRunStatus<'l>> {
+        Ok(skip_whitespace_checks(self.exceptions, file_ctx)?.into())
+    }
+
+    fn cache_fingerprint(&self, _ctx: &ContentContext<'_>) -> Option<String> {
+        // Exceptions only affect pre_run, so there's nothing else to cover.
+        Some(String::new())
     }
 
     fn run<'l>(
@@ -75,13 +131,29 @@ impl ContentLinter for TrailingWhitespace<'_> {
             None => return Ok(RunStatus::Skipped(SkipReason::NonUtf8Content)),
         };
 
-        for (ln, line) in content.lines().enumerate().map(|(ln, line)| (ln + 1, line)) {
-            if line.trim_end() != line {
-                out.write(
-                    LintLevel::Error,
-                    format!("trailing whitespace at line {}", ln),
+        let file_path = ctx.file_ctx().file_path();
+        let mut line_start = 0;
+        for raw_line in content.split_inclusive('\n') {
+            // Strip the line ending the same way str::lines does.
+            let line = match raw_line.strip_suffix('\n') {
+                Some(line) => line.strip_suffix('\r').unwrap_or(line),
+                None => raw_line,
+            };
+            let trimmed_len = line.trim_end().len();
+            if trimmed_len != line.len() {
+                let range = (line_start + trimmed_len)..(line_start + line.len());
+                let span = LintSpan::from_byte_range(content, range.clone());
+                let fix = LintFix::new(
+                    "remove trailing whitespace",
+                    vec![TextEdit::delete(file_path, range)],
+                );
+                out.write_message(
+                    LintMessage::new(LintLevel::Error, "trailing whitespace")
+                        .with_span(span)
+                        .with_fix(fix),
                 );
             }
+            line_start += raw_line.len();
         }
 
         if content
@@ -91,7 +163,25 @@ impl ContentLinter for TrailingWhitespace<'_> {
             .count()
             > 0
         {
-            out.write(LintLevel::Error, "trailing whitespace at EOF");
+            // Point at the empty lines after the last line with any text on it.
+            let text_end = content.trim_end_matches(['\n', '\r']).len();
+            let start = if text_end == 0 {
+                0
+            } else if content[text_end..].starts_with("\r\n") {
+                text_end + 2
+            } else {
+                text_end + 1
+            };
+            let span = LintSpan::from_byte_range(content, start..content.len());
+            let fix = LintFix::new(
+                "remove empty lines at EOF",
+                vec![TextEdit::delete(file_path, start..content.len())],
+            );
+            out.write_message(
+                LintMessage::new(LintLevel::Error, "trailing whitespace at EOF")
+                    .with_span(span)
+                    .with_fix(fix),
+            );
         }
 
         Ok(RunStatus::Executed)
@@ -114,10 +204,18 @@ pub fn build_exceptions(patterns: &[String]) -> crate::Result<GlobSet> {
         .with_context(|| "error while building globset for whitespace patterns")
 }
 
-fn skip_whitespace_checks<'l>(exceptions: &GlobSet, file: &FilePathContext<'l>) -> RunStatus<'l> {
+fn skip_whitespace_checks<'l>(
+    exceptions: &GlobSet,
+    file: &FilePathContext<'l>,
+) -> Result<RunStatus<'l>> {
     if exceptions.is_match(file.file_path()) {
-        return RunStatus::Skipped(SkipReason::UnsupportedFile(file.file_path()));
+        return Ok(RunStatus::Skipped(SkipReason::UnsupportedFile(
+            file.file_path(),
+        )));
+    }
+    if let Some(reason) = file.classify()?.exemption() {
+        return Ok(RunStatus::Skipped(reason));
     }
 
-    RunStatus::Executed
+    Ok(RunStatus::Executed)
 }
@@ -0,0 +1,211 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Declarative configuration for lints.
+//!
+//! Configuration is read from `nexlint.toml` at the project root, or from the
+//! `[workspace.metadata.nexlint]` table in the root `Cargo.toml`. Each lint is configured in a
+//! `[lints.<name>]` table:
+//!
+//! ```toml
+//! [lints.direct-dep-dups]
+//! level = "warn"
+//! allow = ["syn"]
+//!
+//! [lints.crates-in-crates-directory]
+//! enabled = false
+//! ```
+//!
+//! The `enabled` and `level` keys are understood by every lint. `level` is one of `allow`, `warn`
+//! or `deny`. Any other keys are passed to the lint as its configuration, which is checked even if
+//! the lint is disabled.
+//!
+//! `default-scope` sets what's linted by default when running from a subdirectory of the project:
+//! either the whole `project` (the default), or only the `current-dir`.
+//!
+//! ```toml
+//! default-scope = "current-dir"
+//! ```
+//!
+//! The `[classify]` section configures how files are classified: see the `classify` module for
+//! details.
+
+use crate::{lint::classify::ClassifyConfig, prelude::*};
+use camino::Utf8Path;
+use serde::{
+    de::{DeserializeOwned, Error as _},
+    Deserialize,
+};
+use std::{collections::BTreeMap, fs};
+
+/// The path to the configuration file, relative to the project root.
+pub const CONFIG_PATH: &str = "nexlint.toml";
+
+/// What's linted by default when running from a subdirectory of the project.
+#[derive(Copy, Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DefaultScope {
+    /// The entire project.
+    #[default]
+    Project,
+    /// Only files and packages under the current directory.
+    CurrentDir,
+}
+
+/// Lint configuration for a project.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct NexLintConfig {
+    #[serde(default)]
+    default_scope: DefaultScope,
+    #[serde(default)]
+    lints: BTreeMap<String, LintConfig>,
+    #[serde(default)]
+    classify: ClassifyConfig,
+}
+
+impl NexLintConfig {
+    /// Loads configuration for the project at `project_root`.
+    ///
+    /// Returns the default configuration if neither `nexlint.toml` nor
+    /// `[workspace.metadata.nexlint]` exist, and an error if both do.
+    pub fn load(project_root: &Utf8Path) -> Result<Self> {
+        let config_path = project_root.join(CONFIG_PATH);
+        let from_file = if config_path.exists() {
+            let contents = fs::read_to_string(&config_path)
+                .map_err(|e| SystemError::io("reading nexlint config", e))?;
+            let config = toml::from_str(&contents)
+                .map_err(|e| SystemError::de(format!("parsing {}", CONFIG_PATH), e))?;
+            Some(config)
+        } else {
+            None
+        };
+
+        let from_metadata = Self::from_workspace_metadata(&project_root.join("Cargo.toml"))?;
+
+        match (from_file, from_metadata) {
+            (Some(_), Some(_)) => Err(SystemError::de(
+                "reading nexlint config",
+                toml::de::Error::custom(format!(
+                    "config found in both {} and [workspace.metadata.nexlint] \
+                     (only one may be specified)",
+                    CONFIG_PATH
+                )),
+            )),
+            (Some(config), None) | (None, Some(config)) => Ok(config),
+            (None, None) => Ok(Self::default()),
+        }
+    }
+
+    fn from_workspace_metadata(cargo_toml_path: &Utf8Path) -> Result<Option<Self>> {
+        if !cargo_toml_path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(cargo_toml_path)
+            .map_err(|e| SystemError::io("reading root Cargo.toml", e))?;
+        let mut cargo_toml: toml::Value =
+            toml::from_str(&contents).map_err(|e| SystemError::de("parsing root Cargo.toml", e))?;
+
+        let metadata = cargo_toml
+            .get_mut("workspace")
+            .and_then(|workspace| workspace.get_mut("metadata"))
+            .and_then(|metadata| metadata.as_table_mut())
+            .and_then(|metadata| metadata.remove("nexlint"));
+        metadata
+            .map(|metadata| {
+                metadata
+                    .try_into()
+                    .map_err(|e| SystemError::de("parsing [workspace.metadata.nexlint]", e))
+            })
+            .transpose()
+    }
+
+    /// Returns the configuration for the given lint, if any.
+    pub fn lint(&self, name: &str) -> Option<&LintConfig> {
+        self.lints.get(name)
+    }
+
+    /// Returns what's linted by default when running from a subdirectory of the project.
+    pub fn default_scope(&self) -> DefaultScope {
+        self.default_scope
+    }
+
+    /// Returns the configuration for the file classifier.
+    pub fn classify(&self) -> &ClassifyConfig {
+        &self.classify
+    }
+
+    /// Returns the names of all configured lints.
+    pub fn lint_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.lints.keys().map(|name| name.as_str())
+    }
+
+    /// Returns whether the given lint is enabled, using `default` if it isn't configured.
+    pub fn is_enabled(&self, name: &str, default: bool) -> bool {
+        self.lint(name)
+            .and_then(|config| config.enabled)
+            .unwrap_or(default)
+    }
+
+    /// Returns the level configured for the given lint, if any.
+    pub fn level(&self, name: &str) -> Option<OverrideLevel> {
+        self.lint(name).and_then(|config| config.level)
+    }
+
+    /// Deserializes the lint-specific configuration for the given lint.
+    ///
+    /// If the lint isn't configured, `T` is deserialized from an empty table. To report unknown
+    /// keys as errors, `T` should use `#[serde(deny_unknown_fields)]`.
+    pub fn lint_config<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        let table = self
+            .lint(name)
+            .map(|config| config.config.clone())
+            .unwrap_or_default();
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| SystemError::de(format!("parsing config for lint '{}'", name), e))
+    }
+
+    /// Returns an error if any configured lint isn't in `known`.
+    pub fn check_lint_names<'a>(&self, known: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        let known: Vec<_> = known.into_iter().collect();
+        let unknown: Vec<_> = self
+            .lint_names()
+            .filter(|name| !known.contains(name))
+            .collect();
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(SystemError::de(
+                "reading nexlint config",
+                toml::de::Error::custom(format!(
+                    "unknown lints: {} (known lints: {})",
+                    unknown.join(", "),
+                    known.join(", ")
+                )),
+            ))
+        }
+    }
+}
+
+/// Configuration for a single lint.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LintConfig {
+    enabled: Option<bool>,
+    level: Option<OverrideLevel>,
+    #[serde(flatten)]
+    config: toml::value::Table,
+}
+
+impl LintConfig {
+    /// Returns whether this lint is explicitly enabled or disabled.
+    pub fn enabled(&self) -> Option<bool> {
+        self.enabled
+    }
+
+    /// Returns the level messages from this lint are reported at, if overridden.
+    pub fn level(&self) -> Option<OverrideLevel> {
+        self.level
+    }
+}
@@ -3,6 +3,7 @@
 
 use camino::{FromPathBufError, Utf8PathBuf};
 use hex::FromHexError;
+use rayon::ThreadPoolBuildError;
 use serde::{de, ser};
 use std::{borrow::Cow, error, fmt, io, process::ExitStatus, result, str::Utf8Error};
 
@@ -20,8 +21,21 @@ pub enum SystemError {
     Exec {
         cmd: &'static str,
         status: ExitStatus,
+        /// The command's standard error, if it was captured rather than passed through.
+        stderr: Option<String>,
     },
     GitRoot(Cow<'static, str>),
+    MissingPreRunState {
+        type_name: &'static str,
+    },
+    PathNotInProjectRoot {
+        path: Utf8PathBuf,
+        project_root: Utf8PathBuf,
+    },
+    Glob {
+        context: Cow<'static, str>,
+        err: globset::Error,
+    },
     FromHex {
         context: Cow<'static, str>,
         err: FromHexError,
@@ -46,6 +60,12 @@ pub enum SystemError {
         context: Cow<'static, str>,
         err: Box<dyn error::Error + Send + Sync>,
     },
+    ThreadPool(ThreadPoolBuildError),
+    UnknownLint {
+        pattern: String,
+        known: Vec<&'static str>,
+    },
+    UnknownPackages(Vec<String>),
 }
 
 impl SystemError {
@@ -74,6 +94,13 @@ impl SystemError {
         SystemError::GitRoot(msg.into())
     }
 
+    pub fn glob(context: impl Into<Cow<'static, str>>, err: globset::Error) -> Self {
+        SystemError::Glob {
+            context: context.into(),
+            err,
+        }
+    }
+
     pub fn from_hex(context: impl Into<Cow<'static, str>>, err: FromHexError) -> Self {
         SystemError::FromHex {
             context: context.into(),
@@ -113,15 +140,44 @@ impl fmt::Display for SystemError {
                 "current dir {} not in project root {}",
                 current_dir, project_root,
             ),
-            SystemError::Exec { cmd, status } => match status.code() {
-                Some(code) => write!(f, "'{}' failed with exit code {}", cmd, code),
-                None => write!(f, "'{}' terminated by signal", cmd),
-            },
+            SystemError::Exec {
+                cmd,
+                status,
+                stderr,
+            } => {
+                match status.code() {
+                    Some(code) => write!(f, "'{}' failed with exit code {}", cmd, code)?,
+                    None => write!(f, "'{}' terminated by signal", cmd)?,
+                }
+                match stderr {
+                    Some(stderr) if !stderr.is_empty() => write!(f, ":\n{}", stderr),
+                    _ => Ok(()),
+                }
+            }
             SystemError::GitRoot(s) => write!(f, "git root error: {}", s),
+            SystemError::MissingPreRunState { type_name } => write!(
+                f,
+                "pre_run didn't return state of type {} (use PreRunStatus::with_state)",
+                type_name
+            ),
+            SystemError::PathNotInProjectRoot { path, project_root } => {
+                write!(f, "path {} not in project root {}", path, project_root)
+            }
             SystemError::NonUtf8Path { path, .. } => {
                 write!(f, "non-UTF-8 path \"{}\"", String::from_utf8_lossy(path))
             }
+            SystemError::ThreadPool(_) => write!(f, "while building lint thread pool"),
+            SystemError::UnknownLint { pattern, known } => write!(
+                f,
+                "no linters match '{}' (available linters: {})",
+                pattern,
+                known.join(", ")
+            ),
+            SystemError::UnknownPackages(names) => {
+                write!(f, "unknown workspace packages: {}", names.join(", "))
+            }
             SystemError::FromHex { context, .. }
+            | SystemError::Glob { context, .. }
             | SystemError::Io { context, .. }
             | SystemError::Serde { context, .. }
             | SystemError::Camino { context, .. }
@@ -135,13 +191,19 @@ impl error::Error for SystemError {
         match self {
             SystemError::CwdNotInProjectRoot { .. }
             | SystemError::Exec { .. }
-            | SystemError::GitRoot(_) => None,
+            | SystemError::GitRoot(_)
+            | SystemError::MissingPreRunState { .. }
+            | SystemError::PathNotInProjectRoot { .. }
+            | SystemError::UnknownLint { .. }
+            | SystemError::UnknownPackages(_) => None,
             SystemError::FromHex { err, .. } => Some(err),
+            SystemError::Glob { err, .. } => Some(err),
             SystemError::Io { err, .. } => Some(err),
             SystemError::Guppy { err, .. } => Some(err),
             SystemError::NonUtf8Path { err, .. } => Some(err),
             SystemError::Camino { err, .. } => Some(err),
             SystemError::Serde { err, .. } => Some(err.as_ref()),
+            SystemError::ThreadPool(err) => Some(err),
         }
     }
 }
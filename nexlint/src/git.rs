@@ -4,12 +4,15 @@
 use crate::errors::*;
 use camino::{Utf8Path, Utf8PathBuf};
 use determinator::Utf8Paths0;
+use guppy::{graph::PackageGraph, MetadataCommand};
 use once_cell::sync::OnceCell;
 use std::{
     borrow::Cow,
     ffi::{OsStr, OsString},
-    fmt,
+    fmt, fs,
+    io::Write,
     process::{Command, Stdio},
+    str, thread,
 };
 
 /// Support for source control operations through running Git commands.
@@ -25,9 +28,9 @@ pub struct GitCli {
 }
 
 impl GitCli {
-    /// Creates a new instance of the Git CLI.
-    pub fn new() -> Result<Self> {
-        let root = Self::repository_root()?;
+    /// Creates a new instance of the Git CLI for the repository containing `dir`.
+    pub fn new(dir: &Utf8Path) -> Result<Self> {
+        let root = Self::repository_root(dir)?;
         Ok(Self {
             root,
             tracked_files: OnceCell::new(),
@@ -55,6 +58,7 @@ impl GitCli {
                 return Err(SystemError::Exec {
                     cmd: "git ls-files",
                     status: output.status,
+                    stderr: None,
                 });
             }
 
@@ -76,6 +80,7 @@ impl GitCli {
             return Err(SystemError::Exec {
                 cmd: "git merge-base",
                 status: output.status,
+                stderr: None,
             });
         }
 
@@ -112,6 +117,7 @@ impl GitCli {
             return Err(SystemError::Exec {
                 cmd: "git diff",
                 status: output.status,
+                stderr: None,
             });
         }
 
@@ -119,13 +125,104 @@ impl GitCli {
             .map_err(|(path, err)| SystemError::NonUtf8Path { path, err })
     }
 
+    /// Returns the values of the given attributes (as set in `.gitattributes` files) for each of
+    /// the given paths, as `(path, attribute, value)` triples.
+    ///
+    /// Values are `set`, `unset`, or the value the attribute was set to. Attributes that are
+    /// unspecified for a path are left out.
+    pub fn check_attr<'a>(
+        &self,
+        attrs: &[&str],
+        paths: impl IntoIterator<Item = &'a Utf8Path>,
+    ) -> Result<Vec<(Utf8PathBuf, String, String)>> {
+        let mut child = self
+            .git_command()
+            // The -z causes paths to be read and written separated by \0, without quoting.
+            .args(["check-attr", "-z", "--stdin"])
+            .args(attrs)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| SystemError::io("running git check-attr", err))?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let paths: Vec<_> = paths.into_iter().collect();
+        // Write paths from another thread so that a full stdout pipe can't deadlock the two.
+        let output = thread::scope(|scope| {
+            let writer = scope.spawn(move || {
+                for path in paths {
+                    stdin.write_all(path.as_str().as_bytes())?;
+                    stdin.write_all(b"\0")?;
+                }
+                Ok(())
+            });
+            let output = child.wait_with_output();
+            writer
+                .join()
+                .expect("writer thread doesn't panic")
+                .and(output)
+        })
+        .map_err(|err| SystemError::io("running git check-attr", err))?;
+        if !output.status.success() {
+            return Err(SystemError::Exec {
+                cmd: "git check-attr",
+                status: output.status,
+                stderr: None,
+            });
+        }
+
+        // The output is a list of path, attribute and value fields, each followed by a \0.
+        let fields = output
+            .stdout
+            .split(|&b| b == 0)
+            .map(|field| {
+                str::from_utf8(field).map_err(|err| SystemError::NonUtf8Path {
+                    path: field.to_vec(),
+                    err,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(fields
+            .chunks_exact(3)
+            .filter(|triple| triple[2] != "unspecified")
+            .map(|triple| (triple[0].into(), triple[1].to_owned(), triple[2].to_owned()))
+            .collect())
+    }
+
+    /// Returns the package graph for the workspace at the given commit.
+    ///
+    /// The commit is checked out into a scratch worktree at `target/nexlint-scratch/tree`, which is
+    /// registered with the repository through `git worktree add` and reused across invocations.
+    /// Any changes made within the scratch worktree are discarded. Concurrent invocations wait for
+    /// each other to finish with the worktree.
+    pub fn package_graph_at(&self, commit: &GitHash) -> Result<PackageGraph> {
+        // Held until the package graph is built, since that reads from the worktree.
+        let _lock = self.lock_scratch()?;
+        let scratch_dir = self.get_or_init_scratch(commit)?;
+
+        // If the lockfile isn't checked in, Cargo would resolve dependencies from scratch in the
+        // worktree, and the differences would show up as changes. Use the current lockfile instead.
+        let lockfile = self.root.join("Cargo.lock");
+        if lockfile.exists() && !self.is_tracked_at(commit, "Cargo.lock")? {
+            fs::copy(&lockfile, scratch_dir.join("Cargo.lock"))
+                .map_err(|err| SystemError::io("copying Cargo.lock to scratch worktree", err))?;
+        }
+
+        let mut cmd = MetadataCommand::new();
+        cmd.current_dir(&scratch_dir);
+        cmd.build_graph().map_err(|err| {
+            SystemError::guppy(format!("building package graph at {:x}", commit), err)
+        })
+    }
+
     // ---
     // Helper methods
     // ---
 
-    // Attempt to query for the root of the repository
-    fn repository_root() -> Result<Utf8PathBuf> {
+    // Attempt to query for the root of the repository containing dir
+    fn repository_root(dir: &Utf8Path) -> Result<Utf8PathBuf> {
         let output = Command::new("git")
+            .current_dir(dir)
             .args(["rev-parse", "--show-toplevel"])
             .stderr(Stdio::inherit())
             .output()
@@ -146,6 +243,112 @@ impl GitCli {
             })
     }
 
+    // Returns true if the given path is tracked at the given commit.
+    fn is_tracked_at(&self, commit: &GitHash, path: &str) -> Result<bool> {
+        let output = self
+            .git_command()
+            .args(["cat-file", "-e", &format!("{:x}:{}", commit, path)])
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|err| SystemError::io("running git cat-file", err))?;
+        Ok(output.status.success())
+    }
+
+    // Take an exclusive lock on the scratch worktree, waiting for any other process holding it.
+    fn lock_scratch(&self) -> Result<fs::File> {
+        let mut lock_path = self.root.join("target");
+        lock_path.extend(["nexlint-scratch", "lock"]);
+        // The worktree itself is deleted when it's recreated, so the lock file lives next to it.
+        fs::create_dir_all(lock_path.parent().expect("lock path has a parent"))
+            .map_err(|err| SystemError::io("creating scratch dir", err))?;
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|err| SystemError::io(format!("opening lock file at {}", lock_path), err))?;
+        file.lock()
+            .map_err(|err| SystemError::io(format!("locking {}", lock_path), err))?;
+        Ok(file)
+    }
+
+    // Create or update the scratch worktree so that it points at the given commit.
+    fn get_or_init_scratch(&self, commit: &GitHash) -> Result<Utf8PathBuf> {
+        let mut scratch_dir = self.root.join("target");
+        scratch_dir.extend(["nexlint-scratch", "tree"]);
+
+        // The scratch dir is inside the main repository, so `is_git_repo` would always return true
+        // for it. Worktrees have a `.git` file rather than a directory: check for that instead.
+        if scratch_dir.join(".git").is_file() {
+            // Check out the given commit in the existing scratch worktree.
+            let output = self
+                .git_command()
+                .current_dir(&scratch_dir)
+                .args(["reset", "--hard", "-q", &format!("{:x}", commit)])
+                .stderr(Stdio::null())
+                .output()
+                .map_err(|err| SystemError::io("checking out commit in scratch worktree", err))?;
+            if output.status.success() {
+                return Ok(scratch_dir);
+            }
+            // The worktree was left half-created by an earlier run that failed or was interrupted,
+            // or its metadata was removed. Recreate it below.
+        }
+
+        self.remove_scratch(&scratch_dir)?;
+        let output = self
+            .git_command()
+            .args([
+                "worktree",
+                "add",
+                "--detach",
+                scratch_dir.as_str(),
+                &format!("{:x}", commit),
+            ])
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|err| SystemError::io("adding a scratch worktree", err))?;
+        if !output.status.success() {
+            // Don't leave a half-created worktree behind. It's recreated on the next run anyway, so
+            // report the original error rather than any error from cleaning up.
+            let _ = self.remove_scratch(&scratch_dir);
+            return Err(SystemError::Exec {
+                cmd: "git worktree add",
+                status: output.status,
+                stderr: Some(
+                    String::from_utf8_lossy(&output.stderr)
+                        .trim_end()
+                        .to_owned(),
+                ),
+            });
+        }
+
+        Ok(scratch_dir)
+    }
+
+    // Delete the scratch worktree, if it exists, along with Git's metadata for it.
+    fn remove_scratch(&self, scratch_dir: &Utf8Path) -> Result<()> {
+        if scratch_dir.exists() {
+            fs::remove_dir_all(scratch_dir).map_err(|err| {
+                SystemError::io(format!("deleting old scratch dir at {}", scratch_dir), err)
+            })?;
+        }
+        // Clear out any stale worktree metadata left over from a deleted scratch dir.
+        let output = self
+            .git_command()
+            .args(["worktree", "prune"])
+            .output()
+            .map_err(|err| SystemError::io("running git worktree prune", err))?;
+        if !output.status.success() {
+            return Err(SystemError::Exec {
+                cmd: "git worktree prune",
+                status: output.status,
+                stderr: None,
+            });
+        }
+        Ok(())
+    }
+
     // TODO: abstract out command running and error handling
     fn git_command(&self) -> Command {
         // TODO: add support for the GIT environment variable?
@@ -191,3 +394,64 @@ impl fmt::LowerHex for GitHash {
         write!(f, "{}", hex::encode(self.0))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+
+    #[test]
+    fn scratch_worktree_is_recovered() {
+        let repo = TestRepo::new(&[("README.md", "first\n")]);
+        repo.git(&["commit", "-q", "-m", "first"]);
+        let git_cli = GitCli::new(repo.root()).expect("created Git CLI");
+        let first = git_cli.merge_base("HEAD").expect("found HEAD");
+        repo.write("README.md", "second\n");
+        repo.git(&["commit", "-q", "-am", "second"]);
+        let second = git_cli.merge_base("HEAD").expect("found HEAD");
+
+        // Leave behind a worktree that points at metadata that doesn't exist, as if `git worktree
+        // add` had been interrupted.
+        repo.write("target/nexlint-scratch/tree/.git", "gitdir: /nonexistent\n");
+        repo.write("target/nexlint-scratch/tree/README.md", "junk\n");
+
+        let scratch_dir = git_cli
+            .get_or_init_scratch(&first)
+            .expect("recreated worktree");
+        let readme = scratch_dir.join("README.md");
+        assert_eq!(fs::read_to_string(&readme).unwrap(), "first\n");
+
+        // The recreated worktree is reused, and reset to the requested commit.
+        fs::write(&readme, "local change\n").unwrap();
+        let scratch_dir = git_cli
+            .get_or_init_scratch(&second)
+            .expect("reused worktree");
+        assert_eq!(
+            fs::read_to_string(scratch_dir.join("README.md")).unwrap(),
+            "second\n"
+        );
+    }
+
+    #[test]
+    fn scratch_worktree_errors_include_stderr() {
+        let repo = TestRepo::new(&[("README.md", "first\n")]);
+        repo.git(&["commit", "-q", "-m", "first"]);
+        let git_cli = GitCli::new(repo.root()).expect("created Git CLI");
+        let missing = GitHash::from_hex("00".repeat(20)).expect("valid hash");
+
+        let err = git_cli
+            .get_or_init_scratch(&missing)
+            .expect_err("commit doesn't exist");
+        let message = err.to_string();
+        assert!(
+            message.starts_with("'git worktree add' failed with exit code"),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains("fatal:"),
+            "git's error is included: {}",
+            message
+        );
+    }
+}
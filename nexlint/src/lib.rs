@@ -1,32 +1,47 @@
 // Copyright (c) The nextest Contributors
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::git::GitCli;
-use camino::{Utf8Path, Utf8PathBuf};
+use crate::{git::GitCli, lint::classify::FileClassifier};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 use debug_ignore::DebugIgnore;
 use guppy::{graph::PackageGraph, MetadataCommand};
 use hakari::summaries::HakariConfig;
 use once_cell::sync::OnceCell;
 use std::fs;
 
+mod config;
 mod errors;
 mod git;
 mod lint;
+pub mod report;
+#[cfg(test)]
+mod test_utils;
 
+pub use config::{DefaultScope, LintConfig, NexLintConfig, CONFIG_PATH};
 pub use errors::*;
+pub use lint::cache::DEFAULT_CACHE_DIR;
 
 pub mod prelude {
     pub use super::{
+        config::{DefaultScope, LintConfig, NexLintConfig},
         errors::{Result, SystemError},
         lint::{
-            content::{ContentContext, ContentLinter},
+            baseline::{Baseline, BaselineEntry},
+            classify::{FileClass, FileClassifier, Language},
+            content::{ContentContext, ContentLinter, PreRunState, PreRunStatus},
             file_path::{FilePathContext, FilePathLinter},
+            fix::{LintFix, TextEdit},
+            level::{LevelOverride, OverrideLevel},
+            observer::LintObserver,
             package::{PackageContext, PackageLinter},
             project::{ProjectContext, ProjectLinter},
-            runner::{LintEngine, LintEngineConfig, LintResults},
-            LintFormatter, LintKind, LintLevel, LintMessage, LintSource, Linter, RunStatus,
-            SkipReason,
+            runner::{FixResults, LintEngine, LintEngineConfig, LintResults},
+            select::LintSelection,
+            timings::{LintTimings, LinterTiming, TimingActivity, TimingEvent},
+            LineColumn, LintCategory, LintFormatter, LintKind, LintLevel, LintMessage, LintSource,
+            LintSpan, LintSubMessage, Linter, LinterInfo, LinterKind, RunStatus, SkipReason,
         },
+        report::Reporter,
     };
 }
 
@@ -36,14 +51,16 @@ pub struct NexLintContext {
     current_dir: Utf8PathBuf,
     current_rel_dir: Utf8PathBuf,
     git_cli: GitCli,
+    config: NexLintConfig,
     hakari_config: Option<HakariConfig>,
     package_graph: DebugIgnore<OnceCell<PackageGraph>>,
+    file_classifier: OnceCell<FileClassifier>,
 }
 
 impl NexLintContext {
     /// Creates a new NexLintContext.
     pub fn new(current_dir: Utf8PathBuf) -> Result<Self> {
-        let git_cli = GitCli::new()?;
+        let git_cli = GitCli::new(&current_dir)?;
         let current_rel_dir = match current_dir.strip_prefix(git_cli.root()) {
             Ok(rel_dir) => rel_dir.to_path_buf(),
             Err(_) => {
@@ -54,6 +71,8 @@ impl NexLintContext {
             }
         };
 
+        let config = NexLintConfig::load(git_cli.root())?;
+
         let hakari_config_path = git_cli.root().join(hakari::summaries::DEFAULT_CONFIG_PATH);
         let hakari_config = if hakari_config_path.exists() {
             let contents = fs::read_to_string(hakari_config_path)
@@ -71,8 +90,10 @@ impl NexLintContext {
             current_dir,
             current_rel_dir,
             git_cli,
+            config,
             hakari_config,
             package_graph: DebugIgnore(OnceCell::new()),
+            file_classifier: OnceCell::new(),
         })
     }
 
@@ -99,6 +120,31 @@ impl NexLintContext {
         &self.current_rel_dir
     }
 
+    /// Resolves a path relative to the current working directory into a path relative to the
+    /// project root, without accessing the file system.
+    ///
+    /// Returns an error if the path is outside the project.
+    pub fn project_rel_path(&self, path: impl AsRef<Utf8Path>) -> Result<Utf8PathBuf> {
+        let mut resolved = Utf8PathBuf::new();
+        for component in self.current_dir.join(path).components() {
+            match component {
+                Utf8Component::CurDir => {}
+                Utf8Component::ParentDir => {
+                    resolved.pop();
+                }
+                component => resolved.push(component),
+            }
+        }
+
+        match resolved.strip_prefix(self.project_root()) {
+            Ok(rel_path) => Ok(rel_path.to_owned()),
+            Err(_) => Err(SystemError::PathNotInProjectRoot {
+                path: resolved,
+                project_root: self.project_root().to_owned(),
+            }),
+        }
+    }
+
     /// Returns true if x has been run from the project root.
     pub fn current_dir_is_root(&self) -> bool {
         self.current_rel_dir == ""
@@ -109,6 +155,11 @@ impl NexLintContext {
         &self.git_cli
     }
 
+    /// Returns the lint configuration for this workspace.
+    pub fn config(&self) -> &NexLintConfig {
+        &self.config
+    }
+
     /// Returns the package graph for this workspace.
     pub fn package_graph(&self) -> Result<&PackageGraph> {
         self.package_graph.get_or_try_init(|| {
@@ -121,6 +172,13 @@ impl NexLintContext {
         })
     }
 
+    /// Returns the file classifier for this workspace, reading Git attributes for every tracked
+    /// file the first time it's called.
+    pub fn file_classifier(&self) -> Result<&FileClassifier> {
+        self.file_classifier
+            .get_or_try_init(|| FileClassifier::new(self.config.classify(), &self.git_cli))
+    }
+
     /// For a given list of workspace packages, returns a tuple of (known, unknown) packages.
     ///
     /// Initializes the package graph if it isn't already done so, and returns an error if the
@@ -0,0 +1,297 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Baselines of known lint messages.
+//!
+//! A baseline is a checked-in snapshot of the messages produced by a lint run. Messages that are in
+//! the baseline are treated as known, so that a new lint can be turned on across a large project
+//! and only fail on new violations. Entries are keyed by linter name, lint kind and a fingerprint
+//! of the message text, so they aren't invalidated by unrelated edits that move lines around.
+
+use crate::prelude::*;
+use atomicwrites::{AtomicFile, OverwriteBehavior};
+use camino::Utf8Path;
+use serde::{de::Error as _, Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io::Write};
+
+/// The current version of the baseline file format.
+const BASELINE_VERSION: u32 = 1;
+
+/// A snapshot of known lint messages.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Baseline {
+    entries: BTreeMap<BaselineKey, BaselineValue>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct BaselineKey {
+    linter: String,
+    kind: String,
+    fingerprint: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct BaselineValue {
+    message: String,
+    count: usize,
+}
+
+impl Baseline {
+    /// Creates a baseline containing every message in `results`.
+    pub fn from_results(results: &LintResults<'_>) -> Self {
+        let mut entries: BTreeMap<BaselineKey, BaselineValue> = BTreeMap::new();
+        for (source, message) in &results.messages {
+            entries
+                .entry(BaselineKey::new(source, message))
+                .or_insert_with(|| BaselineValue {
+                    message: message.message().to_owned(),
+                    count: 0,
+                })
+                .count += 1;
+        }
+        Self { entries }
+    }
+
+    /// Loads a baseline from the given path.
+    pub fn load(path: &Utf8Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| SystemError::io(format!("reading baseline {}", path), err))?;
+        let file: BaselineFile = serde_json::from_str(&contents)
+            .map_err(|err| SystemError::de(format!("parsing baseline {}", path), err))?;
+        if file.version != BASELINE_VERSION {
+            return Err(SystemError::de(
+                format!("parsing baseline {}", path),
+                serde_json::Error::custom(format!(
+                    "unsupported baseline version {} (expected {})",
+                    file.version, BASELINE_VERSION
+                )),
+            ));
+        }
+
+        let mut entries = BTreeMap::new();
+        for entry in file.entries {
+            let key = BaselineKey {
+                linter: entry.linter,
+                kind: entry.kind,
+                fingerprint: entry.fingerprint,
+            };
+            entries
+                .entry(key)
+                .or_insert_with(|| BaselineValue {
+                    message: entry.message,
+                    count: 0,
+                })
+                .count += entry.count;
+        }
+        Ok(Self { entries })
+    }
+
+    /// Writes out this baseline to the given path atomically.
+    ///
+    /// Entries are sorted so that the file is stable across runs.
+    pub fn write(&self, path: &Utf8Path) -> Result<()> {
+        let file = BaselineFile {
+            version: BASELINE_VERSION,
+            entries: self.entries().collect(),
+        };
+        let mut contents = serde_json::to_vec_pretty(&file)
+            .map_err(|err| SystemError::ser(format!("serializing baseline {}", path), err))?;
+        contents.push(b'\n');
+
+        AtomicFile::new(path, OverwriteBehavior::AllowOverwrite)
+            .write(|f| f.write_all(&contents))
+            .map_err(|err| match err {
+                atomicwrites::Error::Internal(err) | atomicwrites::Error::User(err) => {
+                    SystemError::io(format!("writing baseline {}", path), err)
+                }
+            })
+    }
+
+    /// Returns the number of messages in this baseline.
+    pub fn len(&self) -> usize {
+        self.entries.values().map(|value| value.count).sum()
+    }
+
+    /// Returns true if this baseline has no messages.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entries in this baseline.
+    pub fn entries(&self) -> impl Iterator<Item = BaselineEntry> + '_ {
+        self.entries.iter().map(|(key, value)| BaselineEntry {
+            linter: key.linter.clone(),
+            kind: key.kind.clone(),
+            fingerprint: key.fingerprint.clone(),
+            message: value.message.clone(),
+            count: value.count,
+        })
+    }
+
+    /// Moves messages that are in this baseline from `results.messages` to `results.skipped`, with
+    /// the reason [`SkipReason::Baselined`].
+    ///
+    /// Returns the entries that didn't match as many messages as were recorded, with `count` set to
+    /// the number of unmatched messages. These are stale: the baseline can be updated to remove
+    /// them. Stale entries are only meaningful if `results` came from a run over the whole project.
+    pub fn apply(&self, results: &mut LintResults<'_>) -> Vec<BaselineEntry> {
+        let mut remaining: BTreeMap<&BaselineKey, usize> = self
+            .entries
+            .iter()
+            .map(|(key, value)| (key, value.count))
+            .collect();
+
+        let messages = std::mem::take(&mut results.messages);
+        for (source, message) in messages {
+            let key = BaselineKey::new(&source, &message);
+            match remaining.get_mut(&key) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    results
+                        .skipped
+                        .push((source, SkipReason::Baselined(Box::new(message))));
+                }
+                _ => results.messages.push((source, message)),
+            }
+        }
+
+        remaining
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(key, count)| BaselineEntry {
+                linter: key.linter.clone(),
+                kind: key.kind.clone(),
+                fingerprint: key.fingerprint.clone(),
+                message: self.entries[key].message.clone(),
+                count,
+            })
+            .collect()
+    }
+}
+
+impl BaselineKey {
+    fn new(source: &LintSource<'_>, message: &LintMessage) -> Self {
+        Self {
+            linter: source.name().to_owned(),
+            kind: source.kind().to_string(),
+            fingerprint: message.fingerprint(),
+        }
+    }
+}
+
+/// An entry in a baseline.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct BaselineEntry {
+    /// The name of the linter that produced the message.
+    pub linter: String,
+    /// The kind of lint, as displayed.
+    pub kind: String,
+    /// The fingerprint of the message, as returned by [`LintMessage::fingerprint`].
+    pub fingerprint: String,
+    /// The text of the message. This is informational and not used for matching.
+    pub message: String,
+    /// The number of matching messages.
+    pub count: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+struct BaselineFile {
+    version: u32,
+    entries: Vec<BaselineEntry>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use camino::Utf8PathBuf;
+
+    fn results<'l>(messages: &[(&'l Utf8Path, &'static str)]) -> LintResults<'l> {
+        let mut results = LintResults::new();
+        results.messages = messages
+            .iter()
+            .map(|&(path, message)| {
+                let source = LintSource::new("linter", LintKind::Content(path));
+                (source, LintMessage::new(LintLevel::Error, message))
+            })
+            .collect();
+        results
+    }
+
+    fn texts<'a>(messages: impl IntoIterator<Item = &'a LintMessage>) -> Vec<&'a str> {
+        messages.into_iter().map(LintMessage::message).collect()
+    }
+
+    #[test]
+    fn apply_counts() {
+        let (a, b) = (Utf8Path::new("a.rs"), Utf8Path::new("b.rs"));
+        let baseline = Baseline::from_results(&results(&[
+            (a, "repeated"),
+            (a, "repeated"),
+            (a, "fixed"),
+            (b, "stale twice"),
+            (b, "stale twice"),
+            (b, "once"),
+        ]));
+        assert_eq!(baseline.len(), 6);
+
+        let mut results = results(&[
+            (a, "repeated"),
+            (a, "new"),
+            (a, "repeated"),
+            (a, "repeated"),
+            (b, "once"),
+            // The same message for a different file isn't in the baseline.
+            (a, "once"),
+        ]);
+        let stale = baseline.apply(&mut results);
+
+        let remaining = results.messages.iter().map(|(_, message)| message);
+        assert_eq!(texts(remaining), ["new", "repeated", "once"]);
+        let baselined = results.skipped.iter().map(|(_, reason)| match reason {
+            SkipReason::Baselined(message) => message.as_ref(),
+            other => panic!("unexpected skip reason {:?}", other),
+        });
+        assert_eq!(texts(baselined), ["repeated", "repeated", "once"]);
+
+        let stale: Vec<_> = stale
+            .iter()
+            .map(|entry| (entry.kind.as_str(), entry.message.as_str(), entry.count))
+            .collect();
+        assert_eq!(
+            stale,
+            [
+                ("content a.rs", "fixed", 1),
+                ("content b.rs", "stale twice", 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn fingerprint_is_stable() {
+        // Fingerprints are stored in checked-in baselines, so they must not change between
+        // versions.
+        let message = LintMessage::new(LintLevel::Error, "trailing whitespace");
+        assert_eq!(message.fingerprint(), "ce29b41abc4db443");
+
+        // Only the text of a message is fingerprinted.
+        let moved = LintMessage::new(LintLevel::Warning, "trailing whitespace")
+            .with_span(LintSpan::new(LineColumn::new(3, 1), LineColumn::new(3, 5)))
+            .with_help("remove it");
+        assert_eq!(moved.fingerprint(), message.fingerprint());
+        let other = LintMessage::new(LintLevel::Error, "trailing whitespace at EOF");
+        assert_ne!(other.fingerprint(), message.fingerprint());
+    }
+
+    #[test]
+    fn write_and_load() {
+        let dir = tempfile::tempdir().expect("created temp dir");
+        let path = Utf8PathBuf::try_from(dir.path().join("baseline.json")).expect("valid UTF-8");
+        let a = Utf8Path::new("a.rs");
+        let baseline = Baseline::from_results(&results(&[(a, "one"), (a, "two"), (a, "one")]));
+
+        baseline.write(&path).expect("wrote baseline");
+        assert_eq!(Baseline::load(&path).expect("loaded baseline"), baseline);
+    }
+}
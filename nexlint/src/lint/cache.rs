@@ -0,0 +1,220 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! An on-disk cache of content lint results.
+//!
+//! Each entry holds the messages a content linter reported for one file. Entries are keyed by a
+//! hash of:
+//!
+//! * the linter's name and the fingerprint returned by [`ContentLinter::cache_fingerprint`]
+//! * the path of the file, since linters may treat files differently based on their path
+//! * a hash of the file's contents
+//! * the path and modification time of the running executable, so that rebuilding the linters
+//!   invalidates the cache
+//!
+//! Entries are written to one file each, so that concurrent runs and threads can't corrupt each
+//! other's writes. Stale entries are never cleaned up: delete the cache directory (for example,
+//! with `cargo clean`) to reclaim space.
+
+use crate::prelude::*;
+use atomicwrites::{AtomicFile, OverwriteBehavior};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use std::{env, fs, io::Write, time::UNIX_EPOCH};
+use twox_hash::XxHash3_128;
+
+/// The version of the cache format. Bumping this invalidates all existing entries.
+const CACHE_VERSION: u32 = 1;
+
+/// The default directory for the cache, relative to the project root.
+pub const DEFAULT_CACHE_DIR: &str = "target/nexlint-cache";
+
+/// A cache of content lint results in a directory.
+#[derive(Debug)]
+pub(crate) struct LintCache {
+    dir: Utf8PathBuf,
+    engine_fingerprint: String,
+}
+
+/// The key for a cache entry.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct CacheKey(u128);
+
+#[derive(Deserialize, Serialize)]
+struct CacheEntry {
+    version: u32,
+    messages: Vec<LintMessage>,
+}
+
+impl LintCache {
+    /// Creates a cache that stores entries in `dir`.
+    pub(crate) fn new(dir: &Utf8Path) -> Result<Self> {
+        let exe = env::current_exe()
+            .map_err(|err| SystemError::io("getting path to current executable", err))?;
+        let modified = fs::metadata(&exe)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|err| {
+                SystemError::io(format!("reading metadata for {}", exe.display()), err)
+            })?;
+        let modified = modified
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos());
+
+        Ok(Self {
+            dir: dir.to_owned(),
+            engine_fingerprint: format!("{}\0{}", exe.display(), modified),
+        })
+    }
+
+    /// Returns the key for the results of `linter` against the file in `ctx`, or `None` if the
+    /// linter's results can't be cached.
+    pub(crate) fn key(
+        &self,
+        linter: &dyn ContentLinter,
+        ctx: &ContentContext<'_>,
+    ) -> Option<CacheKey> {
+        let fingerprint = linter.cache_fingerprint(ctx)?;
+        let path = ctx.file_ctx().file_path();
+        let content_hash = XxHash3_128::oneshot(ctx.content_bytes());
+
+        let mut hasher = XxHash3_128::new();
+        for field in [
+            CACHE_VERSION.to_string().as_bytes(),
+            self.engine_fingerprint.as_bytes(),
+            linter.name().as_bytes(),
+            fingerprint.as_bytes(),
+            path.as_str().as_bytes(),
+            &content_hash.to_le_bytes(),
+        ] {
+            // Prefix each field with its length so that different splits can't collide.
+            hasher.write(&field.len().to_le_bytes());
+            hasher.write(field);
+        }
+        Some(CacheKey(hasher.finish_128()))
+    }
+
+    /// Returns the cached messages for `key`, or `None` if there's no usable entry for it.
+    ///
+    /// Entries that can't be read or parsed are treated as missing, and will be overwritten.
+    pub(crate) fn get(&self, key: CacheKey) -> Option<Vec<LintMessage>> {
+        let contents = fs::read(self.entry_path(key)).ok()?;
+        let entry: CacheEntry = serde_json::from_slice(&contents).ok()?;
+        (entry.version == CACHE_VERSION).then_some(entry.messages)
+    }
+
+    /// Stores the messages for `key`.
+    pub(crate) fn put(&self, key: CacheKey, messages: Vec<LintMessage>) -> Result<()> {
+        let path = self.entry_path(key);
+        let parent = path.parent().expect("entry paths have a parent");
+        fs::create_dir_all(parent)
+            .map_err(|err| SystemError::io(format!("creating cache directory {}", parent), err))?;
+
+        let entry = CacheEntry {
+            version: CACHE_VERSION,
+            messages,
+        };
+        let contents = serde_json::to_vec(&entry)
+            .map_err(|err| SystemError::ser("serializing lint cache entry", err))?;
+        AtomicFile::new(&path, OverwriteBehavior::AllowOverwrite)
+            .write(|file| file.write_all(&contents))
+            .map_err(|err| match err {
+                atomicwrites::Error::Internal(err) | atomicwrites::Error::User(err) => {
+                    SystemError::io(format!("writing lint cache entry {}", path), err)
+                }
+            })
+    }
+
+    fn entry_path(&self, key: CacheKey) -> Utf8PathBuf {
+        let hex = format!("{:032x}", key.0);
+        // Spread entries out over subdirectories to keep directory sizes manageable.
+        self.dir.join(&hex[..2]).join(&hex[2..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestRepo, TodoLinter};
+
+    /// Runs `linter` against the repository, returning the number of messages it reported.
+    fn run(repo: &TestRepo, linter: &TodoLinter, level_overrides: &[LevelOverride]) -> usize {
+        let ctx = repo.context();
+        let cache_dir = repo.root().join(DEFAULT_CACHE_DIR);
+        let content_linters: &[&dyn ContentLinter] = &[linter];
+        let engine = LintEngineConfig::new(&ctx)
+            .with_content_linters(content_linters)
+            .with_level_overrides(level_overrides)
+            .with_cache_dir(Some(&cache_dir))
+            .build();
+        let results = engine.run().expect("lint run succeeded");
+        assert_eq!(
+            results.executed.len(),
+            2,
+            "cached results count as executed"
+        );
+        results.messages.len()
+    }
+
+    fn repo() -> TestRepo {
+        TestRepo::new(&[("a.txt", "TODO\n"), ("b.txt", "TODO TODO\n")])
+    }
+
+    #[test]
+    fn cache_hits() {
+        let repo = repo();
+        let linter = TodoLinter::new("todo").with_fingerprint("v1");
+        assert_eq!(run(&repo, &linter, &[]), 3);
+        assert_eq!(linter.runs(), 2);
+
+        // Results are replayed from the cache.
+        assert_eq!(run(&repo, &linter, &[]), 3);
+        assert_eq!(linter.runs(), 2);
+
+        // Level overrides still apply to cached results.
+        let allow = [LevelOverride::new("todo", OverrideLevel::Allow)];
+        assert_eq!(run(&repo, &linter, &allow), 0);
+        assert_eq!(linter.runs(), 2);
+    }
+
+    #[test]
+    fn cache_invalidation() {
+        let repo = repo();
+        let linter = TodoLinter::new("todo").with_fingerprint("v1");
+        assert_eq!(run(&repo, &linter, &[]), 3);
+
+        // Changing a file's contents only invalidates that file.
+        repo.write("a.txt", "no more\n");
+        assert_eq!(run(&repo, &linter, &[]), 2);
+        assert_eq!(linter.runs(), 3);
+
+        // Changing the fingerprint or name of the linter invalidates every file.
+        let linter = TodoLinter::new("todo").with_fingerprint("v2");
+        assert_eq!(run(&repo, &linter, &[]), 2);
+        assert_eq!(linter.runs(), 2);
+        let linter = TodoLinter::new("other-todo").with_fingerprint("v2");
+        assert_eq!(run(&repo, &linter, &[]), 2);
+        assert_eq!(linter.runs(), 2);
+
+        // Linters without a fingerprint are never cached.
+        let linter = TodoLinter::new("todo");
+        run(&repo, &linter, &[]);
+        run(&repo, &linter, &[]);
+        assert_eq!(linter.runs(), 4);
+    }
+
+    #[test]
+    fn unreadable_entries_are_missing() {
+        let dir = tempfile::tempdir().expect("created temp dir");
+        let dir = Utf8Path::from_path(dir.path()).expect("temp dir is valid UTF-8");
+        let cache = LintCache::new(dir).expect("created cache");
+        let key = CacheKey(0x1234);
+        assert_eq!(cache.get(key), None);
+
+        let messages = vec![LintMessage::new(LintLevel::Error, "message")];
+        cache.put(key, messages.clone()).expect("stored entry");
+        assert_eq!(cache.get(key), Some(messages));
+
+        fs::write(cache.entry_path(key), "not JSON").expect("corrupted entry");
+        assert_eq!(cache.get(key), None);
+    }
+}
@@ -0,0 +1,476 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Classification of files by language and origin, shared by all linters.
+//!
+//! A file's language is worked out from, in order of precedence:
+//!
+//! 1. the `linguist-language` attribute in `.gitattributes`
+//! 2. file names and extensions in the `[classify]` section of the configuration
+//! 3. built-in file names and extensions
+//! 4. the interpreter in a `#!` line at the start of the file
+//!
+//! Files are generated if they have the `linguist-generated` attribute, vendored if they have the
+//! `linguist-vendored` attribute, and binary if they have the `binary` attribute or the `text`
+//! attribute is unset. Generated and vendored files can also be listed as globs in the
+//! configuration:
+//!
+//! ```toml
+//! [classify]
+//! generated = ["**/generated/**"]
+//! vendored = ["third-party/**"]
+//!
+//! [classify.extensions]
+//! bzl = "python"
+//!
+//! [classify.filenames]
+//! BUILD = "python"
+//! ```
+
+use crate::{git::GitCli, prelude::*};
+use camino::{Utf8Path, Utf8PathBuf};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    fs::File,
+    io::{self, Read},
+    str::FromStr,
+};
+
+/// The Git attributes consulted while classifying files.
+const GIT_ATTRIBUTES: &[&str] = &[
+    "linguist-language",
+    "linguist-generated",
+    "linguist-vendored",
+    "binary",
+    "text",
+];
+
+/// The number of bytes read from the start of a file to look for a `#!` line.
+const SHEBANG_CUTOFF: usize = 256;
+
+/// A language that a file can be written in.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum Language {
+    #[serde(rename = "javascript")]
+    JavaScript,
+    Json,
+    Markdown,
+    Move,
+    Proto,
+    Python,
+    Rust,
+    Shell,
+    Toml,
+    #[serde(rename = "typescript")]
+    TypeScript,
+    Yaml,
+}
+
+impl Language {
+    /// Returns the language for a built-in file extension.
+    fn from_extension(extension: &str) -> Option<Self> {
+        let language = match extension {
+            "js" | "jsx" | "cjs" | "mjs" => Language::JavaScript,
+            "json" => Language::Json,
+            "md" => Language::Markdown,
+            "move" => Language::Move,
+            "proto" => Language::Proto,
+            "py" => Language::Python,
+            "rs" => Language::Rust,
+            "sh" | "bash" | "zsh" => Language::Shell,
+            "toml" => Language::Toml,
+            "ts" | "tsx" | "mts" | "cts" => Language::TypeScript,
+            "yml" | "yaml" => Language::Yaml,
+            _ => return None,
+        };
+        Some(language)
+    }
+
+    /// Returns the language for a built-in file name.
+    fn from_file_name(file_name: &str) -> Option<Self> {
+        match file_name {
+            "Cargo.lock" => Some(Language::Toml),
+            _ => None,
+        }
+    }
+
+    /// Returns the language for an interpreter named in a `#!` line.
+    fn from_interpreter(interpreter: &str) -> Option<Self> {
+        match interpreter {
+            "sh" | "bash" | "zsh" | "dash" | "ksh" => Some(Language::Shell),
+            "node" => Some(Language::JavaScript),
+            _ if interpreter.starts_with("python") => Some(Language::Python),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of this language, as used in configuration.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::JavaScript => "javascript",
+            Language::Json => "json",
+            Language::Markdown => "markdown",
+            Language::Move => "move",
+            Language::Proto => "proto",
+            Language::Python => "python",
+            Language::Rust => "rust",
+            Language::Shell => "shell",
+            Language::Toml => "toml",
+            Language::TypeScript => "typescript",
+            Language::Yaml => "yaml",
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Language {
+    type Err = ();
+
+    /// Parses a language name case-insensitively, also accepting the names GitHub Linguist uses.
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let language = match s.to_ascii_lowercase().as_str() {
+            "javascript" => Language::JavaScript,
+            "json" => Language::Json,
+            "markdown" => Language::Markdown,
+            "move" => Language::Move,
+            "proto" | "protocol buffer" | "protocol-buffer" => Language::Proto,
+            "python" => Language::Python,
+            "rust" => Language::Rust,
+            "shell" => Language::Shell,
+            "toml" => Language::Toml,
+            "typescript" => Language::TypeScript,
+            "yaml" => Language::Yaml,
+            _ => return Err(()),
+        };
+        Ok(language)
+    }
+}
+
+/// How a file was classified.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct FileClass {
+    language: Option<Language>,
+    generated: bool,
+    vendored: bool,
+    binary: bool,
+}
+
+impl FileClass {
+    /// Returns the language this file is written in, if known.
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    /// Returns true if this file is written in the given language.
+    pub fn is(&self, language: Language) -> bool {
+        self.language == Some(language)
+    }
+
+    /// Returns true if this file is generated from some other source.
+    pub fn is_generated(&self) -> bool {
+        self.generated
+    }
+
+    /// Returns true if this file is vendored from another project.
+    pub fn is_vendored(&self) -> bool {
+        self.vendored
+    }
+
+    /// Returns true if this file is marked as binary in `.gitattributes`.
+    pub fn is_binary(&self) -> bool {
+        self.binary
+    }
+
+    /// Returns the reason to skip linting this file's contents, if it's binary, generated or
+    /// vendored.
+    pub fn exemption(&self) -> Option<SkipReason<'static>> {
+        if self.binary {
+            Some(SkipReason::BinaryFile)
+        } else if self.generated {
+            Some(SkipReason::GeneratedFile)
+        } else if self.vendored {
+            Some(SkipReason::VendoredFile)
+        } else {
+            None
+        }
+    }
+}
+
+/// Configuration for the file classifier, in the `[classify]` section.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ClassifyConfig {
+    /// Languages for file extensions, overriding the built-in ones.
+    #[serde(default)]
+    extensions: BTreeMap<String, Language>,
+    /// Languages for file names, overriding extensions.
+    #[serde(default)]
+    filenames: BTreeMap<String, Language>,
+    /// Globs for generated files.
+    #[serde(default)]
+    generated: Vec<String>,
+    /// Globs for vendored files.
+    #[serde(default)]
+    vendored: Vec<String>,
+}
+
+/// Classifies files in a project. Obtained through [`ProjectContext::file_classifier`].
+#[derive(Debug)]
+pub struct FileClassifier {
+    config: ClassifyConfig,
+    generated: GlobSet,
+    vendored: GlobSet,
+    attributes: HashMap<Utf8PathBuf, GitAttributes>,
+    project_root: Utf8PathBuf,
+}
+
+#[derive(Debug, Default)]
+struct GitAttributes {
+    language: Option<Language>,
+    generated: Option<bool>,
+    vendored: Option<bool>,
+    binary: bool,
+}
+
+impl FileClassifier {
+    /// Creates a classifier for the files tracked in the project, reading their Git attributes.
+    pub(crate) fn new(config: &ClassifyConfig, git_cli: &GitCli) -> Result<Self> {
+        let generated = build_globs("generated", &config.generated)?;
+        let vendored = build_globs("vendored", &config.vendored)?;
+
+        let tracked_files = git_cli.tracked_files()?;
+        let mut attributes: HashMap<_, GitAttributes> = HashMap::new();
+        for (path, attr, value) in git_cli.check_attr(GIT_ATTRIBUTES, tracked_files.iter())? {
+            let entry = attributes.entry(path).or_default();
+            match attr.as_str() {
+                "linguist-language" => entry.language = value.parse().ok(),
+                "linguist-generated" => entry.generated = Some(is_set(&value)),
+                "linguist-vendored" => entry.vendored = Some(is_set(&value)),
+                "binary" => entry.binary |= is_set(&value),
+                "text" => entry.binary |= value == "unset",
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            config: config.clone(),
+            generated,
+            vendored,
+            attributes,
+            project_root: git_cli.root().to_owned(),
+        })
+    }
+
+    /// Classifies the file at the given path, relative to the project root.
+    pub fn classify(&self, path: &Utf8Path) -> Result<FileClass> {
+        let attributes = self.attributes.get(path);
+
+        let file_name = path.file_name();
+        let extension = path.extension();
+        let mut language = attributes
+            .and_then(|attributes| attributes.language)
+            .or_else(|| self.config.filenames.get(file_name?).copied())
+            .or_else(|| self.config.extensions.get(extension?).copied())
+            .or_else(|| Language::from_file_name(file_name?))
+            .or_else(|| Language::from_extension(extension?));
+        if language.is_none() && extension.is_none() {
+            language = self.read_shebang(path)?;
+        }
+
+        Ok(FileClass {
+            language,
+            generated: attributes
+                .and_then(|attributes| attributes.generated)
+                .unwrap_or_else(|| self.generated.is_match(path)),
+            vendored: attributes
+                .and_then(|attributes| attributes.vendored)
+                .unwrap_or_else(|| self.vendored.is_match(path)),
+            binary: attributes.is_some_and(|attributes| attributes.binary),
+        })
+    }
+
+    /// Returns the language of the interpreter in the file's `#!` line, if any.
+    fn read_shebang(&self, path: &Utf8Path) -> Result<Option<Language>> {
+        let full_path = self.project_root.join(path);
+        let mut buf = Vec::with_capacity(SHEBANG_CUTOFF);
+        match File::open(&full_path) {
+            Ok(file) => file
+                .take(SHEBANG_CUTOFF as u64)
+                .read_to_end(&mut buf)
+                .map_err(|err| SystemError::io(format!("reading {}", full_path), err))?,
+            // Files can be listed by source control but missing -- this is normal.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(SystemError::io(format!("reading {}", full_path), err)),
+        };
+
+        let Some(line) = buf.strip_prefix(b"#!") else {
+            return Ok(None);
+        };
+        let line = line.split(|&b| b == b'\n').next().unwrap_or_default();
+        let line = String::from_utf8_lossy(line);
+        let mut words = line.split_whitespace();
+        let mut interpreter = words.next().and_then(|path| path.rsplit('/').next());
+        if interpreter == Some("env") {
+            // Skip over any flags passed to env, such as -S.
+            interpreter = words.find(|word| !word.starts_with('-'));
+        }
+        Ok(interpreter.and_then(Language::from_interpreter))
+    }
+}
+
+fn is_set(value: &str) -> bool {
+    value == "set" || value == "true"
+}
+
+fn build_globs(kind: &str, globs: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let glob = Glob::new(glob).map_err(|err| {
+            SystemError::glob(format!("parsing {} file glob '{}'", kind, glob), err)
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|err| SystemError::glob(format!("building {} file globs", kind), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+
+    const GITATTRIBUTES: &str = "\
+*.gen.rs linguist-generated
+vendor/** linguist-vendored
+*.png binary
+*.dat -text
+special.bzl linguist-language=Shell
+generated/keep.rs -linguist-generated
+";
+
+    const CONFIG: &str = r#"
+generated = ["generated/**"]
+
+[extensions]
+bzl = "python"
+
+[filenames]
+BUILD = "python"
+"#;
+
+    fn classifier(repo: &TestRepo) -> FileClassifier {
+        let config: ClassifyConfig = toml::from_str(CONFIG).expect("valid config");
+        let git_cli = GitCli::new(repo.root()).expect("created Git CLI");
+        FileClassifier::new(&config, &git_cli).expect("created classifier")
+    }
+
+    fn language(classifier: &FileClassifier, path: &str) -> Option<Language> {
+        classifier
+            .classify(Utf8Path::new(path))
+            .expect("classified file")
+            .language()
+    }
+
+    #[test]
+    fn shebangs() {
+        let repo = TestRepo::new(&[
+            ("scripts/bash", "#!/bin/bash\necho hi\n"),
+            ("scripts/env", "#!/usr/bin/env -S python3 -u\nprint()\n"),
+            ("scripts/node", "#! /usr/bin/node\n"),
+            ("scripts/unknown", "#!/usr/bin/perl\n"),
+            ("scripts/plain", "echo hi\n"),
+            // Shebangs are only read for files without an extension.
+            ("scripts/run.txt", "#!/bin/sh\n"),
+        ]);
+        let classifier = classifier(&repo);
+
+        assert_eq!(language(&classifier, "scripts/bash"), Some(Language::Shell));
+        assert_eq!(language(&classifier, "scripts/env"), Some(Language::Python));
+        assert_eq!(
+            language(&classifier, "scripts/node"),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(language(&classifier, "scripts/unknown"), None);
+        assert_eq!(language(&classifier, "scripts/plain"), None);
+        assert_eq!(language(&classifier, "scripts/run.txt"), None);
+        // Files that are tracked but missing aren't an error.
+        assert_eq!(language(&classifier, "scripts/missing"), None);
+    }
+
+    #[test]
+    fn git_attributes() {
+        let repo = TestRepo::new(&[
+            (".gitattributes", GITATTRIBUTES),
+            ("src/lib.rs", ""),
+            ("src/schema.gen.rs", ""),
+            ("vendor/lib.js", ""),
+            ("image.png", ""),
+            ("data.dat", ""),
+        ]);
+        let classifier = classifier(&repo);
+        let classify = |path| {
+            classifier
+                .classify(Utf8Path::new(path))
+                .expect("classified file")
+        };
+
+        let class = classify("src/lib.rs");
+        assert!(class.is(Language::Rust));
+        assert_eq!(class.exemption(), None);
+
+        let class = classify("src/schema.gen.rs");
+        assert!(class.is_generated());
+        assert_eq!(class.exemption(), Some(SkipReason::GeneratedFile));
+
+        let class = classify("vendor/lib.js");
+        assert!(class.is_vendored());
+        assert!(class.is(Language::JavaScript));
+        assert_eq!(class.exemption(), Some(SkipReason::VendoredFile));
+
+        let class = classify("image.png");
+        assert!(class.is_binary());
+        assert_eq!(class.exemption(), Some(SkipReason::BinaryFile));
+        assert!(classify("data.dat").is_binary(), "unset text means binary");
+    }
+
+    #[test]
+    fn precedence() {
+        let repo = TestRepo::new(&[
+            (".gitattributes", GITATTRIBUTES),
+            ("rules.bzl", ""),
+            ("special.bzl", ""),
+            ("BUILD", "#!/bin/sh\n"),
+            ("generated/out.rs", ""),
+            ("generated/keep.rs", ""),
+        ]);
+        let classifier = classifier(&repo);
+
+        // Configured extensions and file names apply, and file names beat shebangs.
+        assert_eq!(language(&classifier, "rules.bzl"), Some(Language::Python));
+        assert_eq!(language(&classifier, "BUILD"), Some(Language::Python));
+        // The linguist-language attribute beats the configuration.
+        assert_eq!(language(&classifier, "special.bzl"), Some(Language::Shell));
+
+        // Configured globs apply to files without an attribute, and an unset attribute beats them.
+        let is_generated = |path| {
+            classifier
+                .classify(Utf8Path::new(path))
+                .expect("classified file")
+                .is_generated()
+        };
+        assert!(is_generated("generated/out.rs"));
+        assert!(!is_generated("generated/keep.rs"));
+    }
+}
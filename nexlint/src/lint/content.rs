@@ -2,16 +2,30 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::{lint::LintContext, prelude::*};
-use std::str;
+use std::{any::Any, fmt, str};
 
 /// Represents a linter that checks file contents.
 pub trait ContentLinter: Linter {
     /// Pre-run step -- avoids loading the contents if possible.
     ///
-    /// The default implementation returns `Ok(RunStatus::Executed)`; individual lints may configure
-    /// a more restricted set.
-    fn pre_run<'l>(&self, _file_ctx: &FilePathContext<'l>) -> Result<RunStatus<'l>> {
-        Ok(RunStatus::Executed)
+    /// The default implementation returns `Ok(PreRunStatus::Executed(PreRunState::none()))`;
+    /// individual lints may configure a more restricted set. Lints may also return state, such as
+    /// a file type worked out from the path, which is then available to `run` for the same file
+    /// through [`ContentContext::pre_run_state`].
+    fn pre_run<'l>(&self, _file_ctx: &FilePathContext<'l>) -> Result<PreRunStatus<'l>> {
+        Ok(PreRunStatus::Executed(PreRunState::none()))
+    }
+
+    /// Returns a fingerprint of everything other than the file's path and contents that `run`
+    /// depends on for this file, or `None` (the default) if its results must not be cached.
+    ///
+    /// If the lint engine has a cache, results from linters that return a fingerprint are reused
+    /// for files whose path, contents and fingerprint haven't changed, without calling `run`. The
+    /// fingerprint must therefore cover this linter's configuration and any state returned by
+    /// `pre_run`. Messages for other files, and results other than `RunStatus::Executed`, are never
+    /// cached.
+    fn cache_fingerprint(&self, _ctx: &ContentContext<'_>) -> Option<String> {
+        None
     }
 
     /// Executes the lint against the given content context.
@@ -22,10 +36,69 @@ pub trait ContentLinter: Linter {
     ) -> Result<RunStatus<'l>>;
 }
 
+/// The status returned by [`ContentLinter::pre_run`].
+#[derive(Debug)]
+pub enum PreRunStatus<'l> {
+    /// The lint should be run, with the given state passed along to it.
+    Executed(PreRunState),
+    /// This lint was skipped, and the file's contents don't need to be loaded for it.
+    Skipped(SkipReason<'l>),
+}
+
+impl<'l> PreRunStatus<'l> {
+    /// Returns a status indicating that the lint should be run with the given state.
+    pub fn with_state<T: Any + Send + Sync>(state: T) -> Self {
+        PreRunStatus::Executed(PreRunState::new(state))
+    }
+}
+
+impl<'l> From<RunStatus<'l>> for PreRunStatus<'l> {
+    fn from(status: RunStatus<'l>) -> Self {
+        match status {
+            RunStatus::Executed => PreRunStatus::Executed(PreRunState::none()),
+            RunStatus::Skipped(reason) => PreRunStatus::Skipped(reason),
+        }
+    }
+}
+
+/// State computed by [`ContentLinter::pre_run`] and passed along to [`ContentLinter::run`].
+///
+/// The state can be of any type. It's only ever handed back to the linter that created it, for the
+/// same file.
+#[derive(Default)]
+pub struct PreRunState(Option<Box<dyn Any + Send + Sync>>);
+
+impl PreRunState {
+    /// Creates a new pre-run state holding the given value.
+    pub fn new<T: Any + Send + Sync>(state: T) -> Self {
+        Self(Some(Box::new(state)))
+    }
+
+    /// Creates an empty pre-run state.
+    pub fn none() -> Self {
+        Self(None)
+    }
+
+    /// Returns the value held by this state, or `None` if it's empty or holds a different type.
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.0.as_ref()?.downcast_ref()
+    }
+}
+
+impl fmt::Debug for PreRunState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.0 {
+            Some(_) => f.write_str("PreRunState(..)"),
+            None => f.write_str("PreRunState(None)"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ContentContext<'l> {
     file_ctx: FilePathContext<'l>,
     content: Content,
+    pre_run_state: PreRunState,
 }
 
 #[allow(dead_code)]
@@ -40,9 +113,26 @@ impl<'l> ContentContext<'l> {
         Self {
             file_ctx,
             content: Content::new(content),
+            pre_run_state: PreRunState::none(),
         }
     }
 
+    /// Sets the state returned by `pre_run` for the linter that's about to run.
+    pub(super) fn set_pre_run_state(&mut self, state: PreRunState) {
+        self.pre_run_state = state;
+    }
+
+    /// Returns the state that the running linter returned from `pre_run` for this file.
+    ///
+    /// Returns an error if `pre_run` didn't return any state of type `T`.
+    pub fn pre_run_state<T: Any>(&self) -> Result<&T> {
+        self.pre_run_state
+            .get()
+            .ok_or(SystemError::MissingPreRunState {
+                type_name: std::any::type_name::<T>(),
+            })
+    }
+
     /// Returns the file context.
     pub fn file_ctx(&self) -> &FilePathContext<'l> {
         &self.file_ctx
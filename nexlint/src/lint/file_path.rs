@@ -3,6 +3,7 @@
 
 use crate::{lint::LintContext, prelude::*};
 use camino::Utf8Path;
+use once_cell::sync::OnceCell;
 use std::{fs, io};
 
 /// Represents a linter that runs once per file path.
@@ -20,6 +21,7 @@ pub trait FilePathLinter: Linter {
 pub struct FilePathContext<'l> {
     project_ctx: &'l ProjectContext<'l>,
     file_path: &'l Utf8Path,
+    class: OnceCell<FileClass>,
 }
 
 impl<'l> FilePathContext<'l> {
@@ -28,6 +30,7 @@ impl<'l> FilePathContext<'l> {
         Self {
             project_ctx,
             file_path,
+            class: OnceCell::new(),
         }
     }
 
@@ -46,6 +49,13 @@ impl<'l> FilePathContext<'l> {
         self.file_path.extension()
     }
 
+    /// Returns the classification of this file: its language, and whether it's generated, vendored
+    /// or binary.
+    pub fn classify(&self) -> Result<&FileClass> {
+        self.class
+            .get_or_try_init(|| self.project_ctx.file_classifier()?.classify(self.file_path))
+    }
+
     /// Loads this file and turns it into a `ContentContext`.
     ///
     /// Returns `None` if the file is missing.
@@ -0,0 +1,261 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Machine-applicable fixes for lint messages.
+
+use crate::prelude::*;
+use atomicwrites::{AtomicFile, OverwriteBehavior};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, collections::BTreeMap, fs, io::Write, ops::Range};
+
+/// A machine-applicable fix for a lint message.
+///
+/// A fix consists of one or more edits, possibly across several files. Fixes are applied
+/// atomically: either every edit in a fix is applied, or none of them are.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LintFix {
+    description: Cow<'static, str>,
+    edits: Vec<TextEdit>,
+}
+
+impl LintFix {
+    /// Creates a new fix with a short, human-readable description of what it does.
+    pub fn new(description: impl Into<Cow<'static, str>>, edits: Vec<TextEdit>) -> Self {
+        Self {
+            description: description.into(),
+            edits,
+        }
+    }
+
+    /// Returns the description of this fix.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the edits that make up this fix.
+    pub fn edits(&self) -> &[TextEdit] {
+        &self.edits
+    }
+}
+
+/// An edit to a file: replaces a range of bytes with new text.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TextEdit {
+    path: Utf8PathBuf,
+    range: Range<usize>,
+    replacement: String,
+}
+
+impl TextEdit {
+    /// Replaces the given byte range of the file at `path` with `replacement`.
+    ///
+    /// `path` is relative to the project root.
+    pub fn replace(
+        path: impl Into<Utf8PathBuf>,
+        range: Range<usize>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Inserts `text` at the given byte offset of the file at `path`.
+    pub fn insert(path: impl Into<Utf8PathBuf>, offset: usize, text: impl Into<String>) -> Self {
+        Self::replace(path, offset..offset, text)
+    }
+
+    /// Deletes the given byte range of the file at `path`.
+    pub fn delete(path: impl Into<Utf8PathBuf>, range: Range<usize>) -> Self {
+        Self::replace(path, range, "")
+    }
+
+    /// Returns the path of the file this edit applies to, relative to the project root.
+    pub fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    /// Returns the range of bytes this edit replaces.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns the text the range is replaced with.
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+}
+
+/// Applies the given fixes to files under `project_root`, returning the number of fixes applied.
+///
+/// Fixes are considered in order. A fix is skipped if any of its edits is out of bounds or
+/// overlaps with an edit from an earlier fix: re-running linters after applying fixes will suggest
+/// it again if it's still relevant. Each modified file is written out atomically.
+pub(super) fn apply_fixes<'a>(
+    project_root: &Utf8Path,
+    fixes: impl IntoIterator<Item = &'a LintFix>,
+) -> Result<usize> {
+    let mut files: BTreeMap<&Utf8Path, FileEdits> = BTreeMap::new();
+    let mut applied = 0;
+
+    for fix in fixes {
+        // Load any files this fix touches that haven't been seen yet.
+        for edit in &fix.edits {
+            if !files.contains_key(edit.path()) {
+                let full_path = project_root.join(edit.path());
+                let content = fs::read_to_string(&full_path)
+                    .map_err(|err| SystemError::io(format!("reading {} to fix", full_path), err))?;
+                files.insert(edit.path(), FileEdits::new(content));
+            }
+        }
+
+        let applicable = fix.edits.iter().enumerate().all(|(idx, edit)| {
+            files[edit.path()].can_apply(edit)
+                && fix.edits[..idx]
+                    .iter()
+                    .all(|other| other.path() != edit.path() || !overlaps(other, edit))
+        });
+        if applicable {
+            for edit in &fix.edits {
+                files
+                    .get_mut(edit.path())
+                    .expect("file was loaded above")
+                    .edits
+                    .push(edit);
+            }
+            applied += 1;
+        }
+    }
+
+    for (path, file_edits) in files {
+        if file_edits.edits.is_empty() {
+            continue;
+        }
+        let full_path = project_root.join(path);
+        let new_content = file_edits.apply();
+        let atomic_file = AtomicFile::new(&full_path, OverwriteBehavior::AllowOverwrite);
+        atomic_file
+            .write(|f| f.write_all(new_content.as_bytes()))
+            .map_err(|err| match err {
+                atomicwrites::Error::Internal(err) | atomicwrites::Error::User(err) => {
+                    SystemError::io(format!("writing fixes to {}", full_path), err)
+                }
+            })?;
+    }
+
+    Ok(applied)
+}
+
+struct FileEdits<'a> {
+    content: String,
+    edits: Vec<&'a TextEdit>,
+}
+
+impl<'a> FileEdits<'a> {
+    fn new(content: String) -> Self {
+        Self {
+            content,
+            edits: vec![],
+        }
+    }
+
+    fn can_apply(&self, edit: &TextEdit) -> bool {
+        let range = edit.range();
+        range.start <= range.end
+            && self.content.is_char_boundary(range.start)
+            && self.content.is_char_boundary(range.end)
+            && self.edits.iter().all(|other| !overlaps(other, edit))
+    }
+
+    fn apply(mut self) -> String {
+        // Apply edits back to front so that earlier offsets stay valid.
+        self.edits
+            .sort_by_key(|edit| (edit.range.start, edit.range.end));
+        for edit in self.edits.iter().rev() {
+            self.content.replace_range(edit.range(), edit.replacement());
+        }
+        self.content
+    }
+}
+
+fn overlaps(a: &TextEdit, b: &TextEdit) -> bool {
+    // Two insertions at the same offset would be applied in an arbitrary order, so treat them as
+    // overlapping too.
+    (a.range.start < b.range.end && b.range.start < a.range.end) || a.range.start == b.range.start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies fixes to a file called `file.txt` with the given content, returning the number of
+    /// fixes applied and the new content.
+    fn apply(content: &str, fixes: &[LintFix]) -> (usize, String) {
+        let dir = tempfile::tempdir().expect("created temp dir");
+        let root = Utf8Path::from_path(dir.path()).expect("temp dir is valid UTF-8");
+        fs::write(root.join("file.txt"), content).expect("wrote file");
+        let applied = apply_fixes(root, fixes).expect("applied fixes");
+        let content = fs::read_to_string(root.join("file.txt")).expect("read file");
+        (applied, content)
+    }
+
+    fn fix(edits: Vec<TextEdit>) -> LintFix {
+        LintFix::new("fix", edits)
+    }
+
+    #[test]
+    fn apply_fixes_in_any_order() {
+        let fixes = [
+            fix(vec![TextEdit::replace("file.txt", 6..11, "there")]),
+            fix(vec![TextEdit::insert("file.txt", 11, "!")]),
+            fix(vec![TextEdit::delete("file.txt", 0..1)]),
+        ];
+        assert_eq!(apply("hello world", &fixes), (3, "ello there!".to_owned()));
+    }
+
+    #[test]
+    fn overlapping_fixes_are_skipped() {
+        let fixes = [
+            fix(vec![TextEdit::replace("file.txt", 0..5, "howdy")]),
+            // Overlaps with the first fix.
+            fix(vec![TextEdit::replace("file.txt", 3..8, "")]),
+            // Two insertions at the same offset conflict.
+            fix(vec![TextEdit::insert("file.txt", 11, "!")]),
+            fix(vec![TextEdit::insert("file.txt", 11, "?")]),
+            // Edits within a single fix can't overlap either.
+            fix(vec![
+                TextEdit::replace("file.txt", 6..8, "WO"),
+                TextEdit::replace("file.txt", 7..9, "OR"),
+            ]),
+        ];
+        assert_eq!(apply("hello world", &fixes), (2, "howdy world!".to_owned()));
+    }
+
+    #[test]
+    fn fixes_are_applied_atomically() {
+        let fixes = [
+            fix(vec![TextEdit::replace("file.txt", 0..1, "H")]),
+            // The second edit conflicts with the first fix, so neither edit is applied.
+            fix(vec![
+                TextEdit::replace("file.txt", 6..7, "W"),
+                TextEdit::delete("file.txt", 0..5),
+            ]),
+            // Out of bounds.
+            fix(vec![TextEdit::insert("file.txt", 100, "!")]),
+        ];
+        assert_eq!(apply("hello world", &fixes), (1, "Hello world".to_owned()));
+    }
+
+    #[test]
+    fn fixes_must_be_on_char_boundaries() {
+        let fixes = [
+            // The first byte of a two-byte character.
+            fix(vec![TextEdit::delete("file.txt", 0..1)]),
+            fix(vec![TextEdit::delete("file.txt", 2..3)]),
+        ];
+        assert_eq!(apply("éa", &fixes), (1, "é".to_owned()));
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Overrides for the levels that lint messages are reported at.
+
+use crate::prelude::*;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+
+/// The level to report a lint's messages at, overriding the level the linter chose.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverrideLevel {
+    /// Drop messages entirely.
+    Allow,
+    /// Report errors and warnings as warnings. Notes and help messages are left as they are.
+    #[serde(alias = "warning")]
+    Warn,
+    /// Report errors and warnings as errors. Notes and help messages are left as they are.
+    #[serde(alias = "error")]
+    Deny,
+}
+
+/// Overrides the level of messages from a lint, optionally restricted to some paths or a package.
+///
+/// If several overrides match a message, the last one wins.
+#[derive(Clone, Debug)]
+pub struct LevelOverride {
+    lint: String,
+    level: OverrideLevel,
+    paths: Option<GlobSet>,
+    package: Option<String>,
+}
+
+impl LevelOverride {
+    /// Creates a new override for all messages from the lint with the given name.
+    pub fn new(lint: impl Into<String>, level: OverrideLevel) -> Self {
+        Self {
+            lint: lint.into(),
+            level,
+            paths: None,
+            package: None,
+        }
+    }
+
+    /// Restricts this override to messages for paths matching any of the given globs.
+    ///
+    /// Globs are matched against file paths relative to the project root, and against the
+    /// workspace paths of packages for package lints. Project lints never match.
+    pub fn with_paths<'a>(mut self, globs: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for glob in globs {
+            let glob = Glob::new(glob).map_err(|err| {
+                SystemError::glob(format!("parsing level override glob '{}'", glob), err)
+            })?;
+            builder.add(glob);
+        }
+        let paths = builder
+            .build()
+            .map_err(|err| SystemError::glob("building level override globs", err))?;
+        self.paths = Some(paths);
+        Ok(self)
+    }
+
+    /// Restricts this override to messages for the workspace package with the given name.
+    pub fn with_package(mut self, package: impl Into<String>) -> Self {
+        self.package = Some(package.into());
+        self
+    }
+
+    /// Returns the name of the lint this override applies to.
+    pub fn lint(&self) -> &str {
+        &self.lint
+    }
+
+    /// Returns the level this override sets.
+    pub fn level(&self) -> OverrideLevel {
+        self.level
+    }
+
+    fn matches(&self, source: &LintSource<'_>) -> bool {
+        if self.lint != source.name() {
+            return false;
+        }
+        let (path, package) = match source.kind() {
+            LintKind::Project => (None, None),
+            LintKind::Package {
+                name,
+                workspace_path,
+            } => (Some(workspace_path), Some(name)),
+            LintKind::FilePath(path) | LintKind::Content(path) => (Some(path), None),
+        };
+        if let Some(paths) = &self.paths {
+            if !path.is_some_and(|path| paths.is_match(path)) {
+                return false;
+            }
+        }
+        if let Some(expected) = &self.package {
+            if package != Some(expected.as_str()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// The level overrides in effect for a lint run: those passed in to the engine, layered over the
+/// levels in the project's configuration, layered over the linter's default level.
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct Levels<'a> {
+    overrides: &'a [LevelOverride],
+    config: Option<&'a NexLintConfig>,
+    default_level: Option<LintLevel>,
+}
+
+impl<'a> Levels<'a> {
+    pub(super) fn new(overrides: &'a [LevelOverride], config: &'a NexLintConfig) -> Self {
+        Self {
+            overrides,
+            config: Some(config),
+            default_level: None,
+        }
+    }
+
+    /// Reports messages created at the linter's default level at the given level, if there are no
+    /// overrides for them.
+    pub(super) fn with_default_level(mut self, default_level: LintLevel) -> Self {
+        self.default_level = Some(default_level);
+        self
+    }
+
+    /// Applies the override for `source`, if any, to `message`. Returns `None` if the message is
+    /// dropped.
+    ///
+    /// Only errors and warnings are remapped: informational messages keep their level, so that
+    /// denying a lint can't turn its notes into failures.
+    pub(super) fn apply(
+        &self,
+        source: &LintSource<'_>,
+        mut message: LintMessage,
+    ) -> Option<LintMessage> {
+        let level = self
+            .overrides
+            .iter()
+            .rev()
+            .find(|level_override| level_override.matches(source))
+            .map(|level_override| level_override.level)
+            .or_else(|| self.config.and_then(|config| config.level(source.name())));
+
+        match level {
+            Some(OverrideLevel::Allow) => return None,
+            _ if message.level.is_informational() => {}
+            Some(OverrideLevel::Warn) => message.level = LintLevel::Warning,
+            Some(OverrideLevel::Deny) => message.level = LintLevel::Error,
+            None => match self.default_level {
+                Some(default_level) if message.default_level => message.level = default_level,
+                // The linter chose this level, or the default isn't known yet.
+                _ => return Some(message),
+            },
+        }
+        message.default_level = false;
+        Some(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use camino::Utf8Path;
+
+    fn apply(overrides: &[LevelOverride], path: &str, level: LintLevel) -> Option<LintLevel> {
+        apply_message(overrides, path, LintMessage::new(level, "message"))
+    }
+
+    fn apply_message(
+        overrides: &[LevelOverride],
+        path: &str,
+        message: LintMessage,
+    ) -> Option<LintLevel> {
+        let levels = Levels {
+            overrides,
+            config: None,
+            default_level: Some(LintLevel::Warning),
+        };
+        let source = LintSource::new("lint", LintKind::Content(Utf8Path::new(path)));
+        levels
+            .apply(&source, message)
+            .map(|message| message.level())
+    }
+
+    #[test]
+    fn overrides() {
+        use LintLevel::*;
+
+        let deny = [LevelOverride::new("lint", OverrideLevel::Deny)];
+        assert_eq!(apply(&deny, "a.rs", Warning), Some(Error));
+        assert_eq!(apply(&deny, "a.rs", Error), Some(Error));
+        let warn = [LevelOverride::new("lint", OverrideLevel::Warn)];
+        assert_eq!(apply(&warn, "a.rs", Error), Some(Warning));
+        let allow = [LevelOverride::new("lint", OverrideLevel::Allow)];
+        assert_eq!(apply(&allow, "a.rs", Error), None);
+        assert_eq!(apply(&allow, "a.rs", Note), None);
+
+        // Other lints aren't affected, and keep the level they were written with.
+        let other = [LevelOverride::new("other", OverrideLevel::Allow)];
+        assert_eq!(apply(&other, "a.rs", Error), Some(Error));
+        let message = || LintMessage::at_default_level("message");
+        assert_eq!(apply_message(&other, "a.rs", message()), Some(Warning));
+        assert_eq!(apply_message(&deny, "a.rs", message()), Some(Error));
+
+        // The last matching override wins.
+        let overrides = [
+            LevelOverride::new("lint", OverrideLevel::Allow),
+            LevelOverride::new("lint", OverrideLevel::Warn)
+                .with_paths(["src/**"])
+                .expect("valid glob"),
+        ];
+        assert_eq!(apply(&overrides, "src/a.rs", Error), Some(Warning));
+        assert_eq!(apply(&overrides, "tests/a.rs", Error), None);
+    }
+
+    #[test]
+    fn overrides_keep_informational_levels() {
+        use LintLevel::*;
+
+        assert_eq!(apply(&[], "a.rs", Note), Some(Note));
+
+        for level in [OverrideLevel::Warn, OverrideLevel::Deny] {
+            let overrides = [LevelOverride::new("lint", level)];
+            assert_eq!(apply(&overrides, "a.rs", Note), Some(Note));
+            assert_eq!(apply(&overrides, "a.rs", Help), Some(Help));
+        }
+    }
+}
@@ -6,20 +6,179 @@
 //! The overall design is generally inspired by
 //! [Arcanist](https://secure.phabricator.com/book/phabricator/article/arcanist_lint)'s lint engine.
 
+pub mod baseline;
+pub mod cache;
+pub mod classify;
 pub mod content;
 pub mod file_path;
+pub mod fix;
+pub mod level;
+pub mod observer;
 pub mod package;
 pub mod project;
 pub mod runner;
+pub mod select;
+mod suppress;
+pub mod timings;
 
 use camino::Utf8Path;
+use fix::LintFix;
 use guppy::PackageId;
-use std::{borrow::Cow, fmt};
+use level::Levels;
+use serde::{ser::SerializeMap, Deserialize, Serialize, Serializer};
+use std::{borrow::Cow, fmt, ops::Range};
+use twox_hash::XxHash64;
 
 /// Represents a linter.
 pub trait Linter: Send + Sync + fmt::Debug {
     /// Returns the name of the linter.
     fn name(&self) -> &'static str;
+
+    /// Returns a one-line description of what this linter checks.
+    fn description(&self) -> &'static str {
+        ""
+    }
+
+    /// Returns a longer explanation of what this linter checks and why, with an example of how to
+    /// fix the issues it reports where that's helpful. Shown by `x lint --explain`.
+    fn explanation(&self) -> &'static str {
+        ""
+    }
+
+    /// Returns the level that messages created with [`LintMessage::at_default_level`] are reported
+    /// at, unless a level override or the project's configuration sets one. Messages written with an
+    /// explicit level keep it.
+    ///
+    /// The default is `LintLevel::Error`.
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Error
+    }
+
+    /// Returns the category of issues this linter checks for.
+    fn category(&self) -> LintCategory {
+        LintCategory::Other
+    }
+}
+
+/// The phase a linter runs in, determined by which of the linter traits it implements.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinterKind {
+    /// A [`ProjectLinter`](project::ProjectLinter).
+    Project,
+    /// A [`PackageLinter`](package::PackageLinter).
+    Package,
+    /// A [`FilePathLinter`](file_path::FilePathLinter).
+    FilePath,
+    /// A [`ContentLinter`](content::ContentLinter).
+    Content,
+}
+
+impl LinterKind {
+    /// Returns the name of this kind, as used in output.
+    pub fn name(self) -> &'static str {
+        match self {
+            LinterKind::Project => "project",
+            LinterKind::Package => "package",
+            LinterKind::FilePath => "file-path",
+            LinterKind::Content => "content",
+        }
+    }
+}
+
+impl fmt::Display for LinterKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// The category of issues a linter checks for.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum LintCategory {
+    /// Which crates are depended on, and how.
+    Dependencies,
+    /// Which packages are published, and where.
+    Publishing,
+    /// Package metadata in `Cargo.toml`, such as authors and licenses.
+    Metadata,
+    /// Where packages and files are in the repository, and what they're called.
+    Layout,
+    /// The formatting and boilerplate of source files.
+    Style,
+    /// Anything else.
+    Other,
+}
+
+impl LintCategory {
+    /// Returns the name of this category, as used in output.
+    pub fn name(self) -> &'static str {
+        match self {
+            LintCategory::Dependencies => "dependencies",
+            LintCategory::Publishing => "publishing",
+            LintCategory::Metadata => "metadata",
+            LintCategory::Layout => "layout",
+            LintCategory::Style => "style",
+            LintCategory::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for LintCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// A snapshot of the metadata for a linter, along with its kind.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LinterInfo {
+    name: &'static str,
+    kind: LinterKind,
+    category: LintCategory,
+    default_level: LintLevel,
+    description: &'static str,
+    explanation: &'static str,
+}
+
+impl LinterInfo {
+    /// Collects the metadata for the given linter, which runs as `kind`.
+    pub fn new<L: Linter + ?Sized>(linter: &L, kind: LinterKind) -> Self {
+        Self {
+            name: linter.name(),
+            kind,
+            category: linter.category(),
+            default_level: linter.default_level(),
+            description: linter.description(),
+            explanation: linter.explanation(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn kind(&self) -> LinterKind {
+        self.kind
+    }
+
+    pub fn category(&self) -> LintCategory {
+        self.category
+    }
+
+    pub fn default_level(&self) -> LintLevel {
+        self.default_level
+    }
+
+    pub fn description(&self) -> &'static str {
+        self.description
+    }
+
+    pub fn explanation(&self) -> &'static str {
+        self.explanation
+    }
 }
 
 /// Represents common functionality among various `Context` instances.
@@ -39,6 +198,7 @@ trait LintContext<'l> {
 pub struct LintFormatter<'l, 'a> {
     source: LintSource<'l>,
     messages: &'a mut Vec<(LintSource<'l>, LintMessage)>,
+    levels: Levels<'a>,
 }
 
 impl<'l, 'a> LintFormatter<'l, 'a> {
@@ -46,13 +206,39 @@ impl<'l, 'a> LintFormatter<'l, 'a> {
         source: LintSource<'l>,
         messages: &'a mut Vec<(LintSource<'l>, LintMessage)>,
     ) -> Self {
-        Self { source, messages }
+        Self {
+            source,
+            messages,
+            levels: Levels::default(),
+        }
+    }
+
+    /// Applies the given level overrides to messages written to this formatter.
+    pub(crate) fn with_levels(mut self, levels: Levels<'a>) -> Self {
+        self.levels = levels;
+        self
     }
 
     /// Writes a new lint message to this formatter.
     pub fn write(&mut self, level: LintLevel, message: impl Into<Cow<'static, str>>) {
-        self.messages
-            .push((self.source, LintMessage::new(level, message)));
+        self.write_message(LintMessage::new(level, message));
+    }
+
+    /// Writes a new lint message pointing at a span within the file to this formatter.
+    pub fn write_span(
+        &mut self,
+        level: LintLevel,
+        span: LintSpan,
+        message: impl Into<Cow<'static, str>>,
+    ) {
+        self.write_message(LintMessage::new(level, message).with_span(span));
+    }
+
+    /// Writes a lint message constructed by the caller to this formatter.
+    pub fn write_message(&mut self, message: LintMessage) {
+        if let Some(message) = self.levels.apply(&self.source, message) {
+            self.messages.push((self.source, message));
+        }
     }
 
     /// Writes a new lint message to this formatter with a custom kind.
@@ -62,10 +248,10 @@ impl<'l, 'a> LintFormatter<'l, 'a> {
         level: LintLevel,
         message: impl Into<Cow<'static, str>>,
     ) {
-        self.messages.push((
-            LintSource::new(self.source.name(), kind),
-            LintMessage::new(level, message),
-        ));
+        let source = LintSource::new(self.source.name(), kind);
+        if let Some(message) = self.levels.apply(&source, LintMessage::new(level, message)) {
+            self.messages.push((source, message));
+        }
     }
 }
 
@@ -79,7 +265,8 @@ pub enum RunStatus<'l> {
 }
 
 /// The reason for why this lint was skipped.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "reason", content = "detail", rename_all = "kebab-case")]
 #[non_exhaustive]
 pub enum SkipReason<'l> {
     /// This file's content was not valid UTF-8.
@@ -89,17 +276,92 @@ pub enum SkipReason<'l> {
     /// The given file was unsupported by this linter.
     UnsupportedFile(&'l Utf8Path),
     /// The given package was unsupported by this linter.
+    #[serde(serialize_with = "serialize_package_id")]
     UnsupportedPackage(&'l PackageId),
     /// The given file was excepted by a glob rule
     GlobExemption(&'l str),
+    /// No workspace packages were affected by the changes being linted.
+    Unaffected,
+    /// The file is binary, according to `.gitattributes`.
+    BinaryFile,
+    /// The file is generated from some other source.
+    GeneratedFile,
+    /// The file is vendored from another project.
+    VendoredFile,
+    /// The message was suppressed by an inline `nexlint-allow` directive.
+    Suppressed(Box<LintMessage>),
+    /// The message was recorded in the baseline.
+    Baselined(Box<LintMessage>),
     // TODO: Add more reasons.
 }
 
+impl SkipReason<'_> {
+    /// Returns a short, kebab-case name for the kind of reason, without any details.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SkipReason::NonUtf8Content => "non-utf8-content",
+            SkipReason::UnsupportedExtension(_) => "unsupported-extension",
+            SkipReason::UnsupportedFile(_) => "unsupported-file",
+            SkipReason::UnsupportedPackage(_) => "unsupported-package",
+            SkipReason::GlobExemption(_) => "glob-exemption",
+            SkipReason::Unaffected => "unaffected",
+            SkipReason::BinaryFile => "binary-file",
+            SkipReason::GeneratedFile => "generated-file",
+            SkipReason::VendoredFile => "vendored-file",
+            SkipReason::Suppressed(_) => "suppressed",
+            SkipReason::Baselined(_) => "baselined",
+        }
+    }
+}
+
+impl fmt::Display for SkipReason<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SkipReason::NonUtf8Content => write!(f, "content is not valid UTF-8"),
+            SkipReason::UnsupportedExtension(Some(extension)) => {
+                write!(f, "unsupported extension '{}'", extension)
+            }
+            SkipReason::UnsupportedExtension(None) => write!(f, "no extension"),
+            SkipReason::UnsupportedFile(path) => write!(f, "unsupported file {}", path),
+            SkipReason::UnsupportedPackage(package_id) => {
+                write!(f, "unsupported package {}", package_id)
+            }
+            SkipReason::GlobExemption(glob) => write!(f, "exempted by glob '{}'", glob),
+            SkipReason::Unaffected => write!(f, "no affected packages"),
+            SkipReason::BinaryFile => write!(f, "binary file"),
+            SkipReason::GeneratedFile => write!(f, "generated file"),
+            SkipReason::VendoredFile => write!(f, "vendored file"),
+            SkipReason::Suppressed(message) => write!(f, "suppressed: {}", message.message()),
+            SkipReason::Baselined(message) => write!(f, "in baseline: {}", message.message()),
+        }
+    }
+}
+
+fn serialize_package_id<S: Serializer>(
+    package_id: &&PackageId,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(package_id.repr())
+}
+
 /// A message raised by a lint.
-#[derive(Debug)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct LintMessage {
     level: LintLevel,
     message: Cow<'static, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    span: Option<LintSpan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<LintFix>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    children: Vec<LintSubMessage>,
+    /// Whether `level` is a placeholder for the linter's default level.
+    #[serde(
+        default,
+        rename = "default-level",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    default_level: bool,
 }
 
 impl LintMessage {
@@ -107,9 +369,120 @@ impl LintMessage {
         Self {
             level,
             message: message.into(),
+            span: None,
+            fix: None,
+            children: vec![],
+            default_level: false,
+        }
+    }
+
+    /// Creates a new message at the default level of the linter that reports it, as returned by
+    /// [`Linter::default_level`].
+    ///
+    /// Messages created with [`LintMessage::new`] keep the level they were given unless an override
+    /// changes it.
+    pub fn at_default_level(message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            default_level: true,
+            ..Self::new(LintLevel::Error, message)
+        }
+    }
+
+    /// Attaches a span within the file to this message.
+    pub fn with_span(mut self, span: LintSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Attaches a machine-applicable fix to this message.
+    pub fn with_fix(mut self, fix: LintFix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+
+    /// Attaches a note with additional context to this message.
+    pub fn with_note(self, message: impl Into<Cow<'static, str>>) -> Self {
+        self.with_child(LintSubMessage::new(LintLevel::Note, message))
+    }
+
+    /// Attaches a help message suggesting how to address this message.
+    pub fn with_help(self, message: impl Into<Cow<'static, str>>) -> Self {
+        self.with_child(LintSubMessage::new(LintLevel::Help, message))
+    }
+
+    /// Attaches a sub-message constructed by the caller to this message.
+    pub fn with_child(mut self, child: LintSubMessage) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn level(&self) -> LintLevel {
+        self.level
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the span within the file this message points at, if any.
+    pub fn span(&self) -> Option<&LintSpan> {
+        self.span.as_ref()
+    }
+
+    /// Returns the fix suggested for this message, if any.
+    pub fn fix(&self) -> Option<&LintFix> {
+        self.fix.as_ref()
+    }
+
+    /// Returns the notes and help messages attached to this message.
+    pub fn children(&self) -> &[LintSubMessage] {
+        &self.children
+    }
+
+    /// Attaches the lines of source that spans in this message cover.
+    pub(crate) fn attach_source(&mut self, content: &str) {
+        let spans = self
+            .children
+            .iter_mut()
+            .filter_map(|child| child.span.as_mut());
+        for span in self.span.iter_mut().chain(spans) {
+            span.attach_source(content);
+        }
+    }
+
+    /// Returns a fingerprint of this message's text, as a hex string.
+    ///
+    /// The fingerprint doesn't depend on the span, so it stays the same if the code around the
+    /// message moves.
+    pub fn fingerprint(&self) -> String {
+        format!("{:016x}", XxHash64::oneshot(0, self.message.as_bytes()))
+    }
+}
+
+/// A note or help message attached to a [`LintMessage`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LintSubMessage {
+    level: LintLevel,
+    message: Cow<'static, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    span: Option<LintSpan>,
+}
+
+impl LintSubMessage {
+    pub fn new(level: LintLevel, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+            span: None,
         }
     }
 
+    /// Attaches a span within the file to this sub-message.
+    pub fn with_span(mut self, span: LintSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
     pub fn level(&self) -> LintLevel {
         self.level
     }
@@ -117,15 +490,167 @@ impl LintMessage {
     pub fn message(&self) -> &str {
         &self.message
     }
+
+    /// Returns the span within the file this sub-message points at, if any.
+    pub fn span(&self) -> Option<&LintSpan> {
+        self.span.as_ref()
+    }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// A region within a file that a lint message refers to.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LintSpan {
+    start: LineColumn,
+    end: LineColumn,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    byte_range: Option<Range<usize>>,
+    /// The lines of source this span covers, attached by the engine for content lints.
+    #[serde(skip)]
+    source_lines: Option<String>,
+}
+
+impl LintSpan {
+    /// Creates a new span from a start and end position. `end` is exclusive.
+    pub fn new(start: LineColumn, end: LineColumn) -> Self {
+        Self {
+            start,
+            end,
+            byte_range: None,
+            source_lines: None,
+        }
+    }
+
+    /// Creates a new span from a range of byte offsets into `content`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or doesn't lie on `char` boundaries.
+    pub fn from_byte_range(content: &str, byte_range: Range<usize>) -> Self {
+        Self {
+            start: LineColumn::from_offset(content, byte_range.start),
+            end: LineColumn::from_offset(content, byte_range.end),
+            byte_range: Some(byte_range),
+            source_lines: None,
+        }
+    }
+
+    /// Returns the start of this span.
+    pub fn start(&self) -> LineColumn {
+        self.start
+    }
+
+    /// Returns the end of this span. This is exclusive.
+    pub fn end(&self) -> LineColumn {
+        self.end
+    }
+
+    /// Returns the range of byte offsets covered by this span, if it was created from one.
+    pub fn byte_range(&self) -> Option<Range<usize>> {
+        self.byte_range.clone()
+    }
+
+    /// Returns the lines of source this span covers, without trailing newlines, if known.
+    ///
+    /// The first line returned is line `start().line`. Source lines are available for messages
+    /// from content lints.
+    pub fn source_lines(&self) -> Option<impl Iterator<Item = &str> + '_> {
+        self.source_lines.as_deref().map(str::lines)
+    }
+
+    fn attach_source(&mut self, content: &str) {
+        if self.source_lines.is_some() {
+            return;
+        }
+        // A span that ends at the start of a line doesn't cover that line.
+        let last_line = if self.end.line > self.start.line && self.end.column == 1 {
+            self.end.line - 1
+        } else {
+            self.end.line
+        };
+        let lines: Vec<_> = content
+            .lines()
+            .skip(self.start.line.saturating_sub(1))
+            // Spans can be constructed with an end before the start, so avoid underflowing.
+            .take((last_line + 1).saturating_sub(self.start.line))
+            .collect();
+        self.source_lines = Some(lines.join("\n"));
+    }
+}
+
+impl fmt::Display for LintSpan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.start)
+    }
+}
+
+/// A position within a file. Both the line and the column are 1-based.
+///
+/// Columns are counted in Unicode scalar values (`char`s), not bytes.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineColumn {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+
+    /// Computes the position of the given byte offset into `content`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset is out of bounds or doesn't lie on a `char` boundary.
+    pub fn from_offset(content: &str, offset: usize) -> Self {
+        let before = &content[..offset];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for LineColumn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
 #[allow(dead_code)]
 #[non_exhaustive]
 pub enum LintLevel {
     Error,
     Warning,
-    // TODO: add more levels?
+    /// Informational: something that may be worth knowing about, but doesn't need action.
+    Note,
+    /// Informational: a suggestion for how to improve something.
+    Help,
+}
+
+impl LintLevel {
+    /// Returns true if this is an informational level, i.e. `Note` or `Help`.
+    pub fn is_informational(self) -> bool {
+        matches!(self, LintLevel::Note | LintLevel::Help)
+    }
+
+    /// Returns true if this level is at least as severe as `threshold`. Informational levels are
+    /// equally severe.
+    pub fn is_at_least(self, threshold: LintLevel) -> bool {
+        self.severity() >= threshold.severity()
+    }
+
+    fn severity(self) -> u8 {
+        match self {
+            LintLevel::Error => 2,
+            LintLevel::Warning => 1,
+            LintLevel::Note | LintLevel::Help => 0,
+        }
+    }
 }
 
 impl fmt::Display for LintLevel {
@@ -133,19 +658,22 @@ impl fmt::Display for LintLevel {
         match self {
             LintLevel::Error => write!(f, "ERROR"),
             LintLevel::Warning => write!(f, "WARNING"),
+            LintLevel::Note => write!(f, "NOTE"),
+            LintLevel::Help => write!(f, "HELP"),
         }
     }
 }
 
 /// Message source for lints.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize)]
 pub struct LintSource<'l> {
+    #[serde(rename = "linter")]
     name: &'static str,
     kind: LintKind<'l>,
 }
 
 impl<'l> LintSource<'l> {
-    fn new(name: &'static str, kind: LintKind<'l>) -> Self {
+    pub(crate) fn new(name: &'static str, kind: LintKind<'l>) -> Self {
         Self { name, kind }
     }
 
@@ -169,6 +697,22 @@ pub enum LintKind<'l> {
     Content(&'l Utf8Path),
 }
 
+impl<'l> LintKind<'l> {
+    /// Returns the path of the file this lint refers to, relative to the project root.
+    ///
+    /// Project lints refer to the root `Cargo.toml`, and package lints refer to the package's
+    /// `Cargo.toml`.
+    pub fn file_path(&self) -> Cow<'l, Utf8Path> {
+        match self {
+            LintKind::Project => Cow::Borrowed(Utf8Path::new("Cargo.toml")),
+            LintKind::Package { workspace_path, .. } => {
+                Cow::Owned(workspace_path.join("Cargo.toml"))
+            }
+            LintKind::FilePath(path) | LintKind::Content(path) => Cow::Borrowed(path),
+        }
+    }
+}
+
 impl fmt::Display for LintKind<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -182,3 +726,60 @@ impl fmt::Display for LintKind<'_> {
         }
     }
 }
+
+impl Serialize for LintKind<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        match self {
+            LintKind::Project => {
+                map.serialize_entry("type", "project")?;
+            }
+            LintKind::Package {
+                name,
+                workspace_path,
+            } => {
+                map.serialize_entry("type", "package")?;
+                map.serialize_entry("name", name)?;
+                map.serialize_entry("workspace-path", workspace_path)?;
+            }
+            LintKind::FilePath(path) => {
+                map.serialize_entry("type", "file-path")?;
+                map.serialize_entry("path", path)?;
+            }
+            LintKind::Content(path) => {
+                map.serialize_entry("type", "content")?;
+                map.serialize_entry("path", path)?;
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_lines(start: LineColumn, end: LineColumn, content: &str) -> Vec<String> {
+        let mut span = LintSpan::new(start, end);
+        span.attach_source(content);
+        span.source_lines()
+            .expect("source was attached")
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn attach_source() {
+        let content = "one\ntwo\nthree\n";
+        let at = LineColumn::new;
+        assert_eq!(source_lines(at(2, 1), at(2, 4), content), ["two"]);
+        assert_eq!(
+            source_lines(at(1, 2), at(3, 2), content),
+            ["one", "two", "three"]
+        );
+        // A span ending at the start of a line doesn't cover it.
+        assert_eq!(source_lines(at(1, 1), at(2, 1), content), ["one"]);
+        // Invalid spans don't cover any lines, rather than panicking.
+        assert!(source_lines(at(3, 1), at(1, 1), content).is_empty());
+    }
+}
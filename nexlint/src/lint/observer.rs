@@ -0,0 +1,37 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Observers that receive lint events as a run progresses.
+
+use crate::prelude::*;
+use std::fmt;
+
+/// Receives events from a [`LintEngine`] while it runs, for progress output or incremental
+/// reporting. The complete [`LintResults`] are still returned once the run finishes.
+///
+/// Every method has an empty default implementation, so observers only need to implement the
+/// events they care about.
+///
+/// If the engine uses more than one thread, methods are called on the thread the linter runs on,
+/// so events for different targets can interleave. Events for a single linter and target are
+/// always delivered in order: `linter_started`, then any `message` and `skipped` events, then
+/// `linter_finished`. A content linter that's skipped before its file is loaded only gets a
+/// `skipped` event, and messages about unused `nexlint-allow` directives come from the engine
+/// itself rather than from a running linter.
+///
+/// Events describe linters as they run. They don't account for later processing: a run stopped by
+/// `fail_fast` may discard messages that were already observed, and messages may later be moved
+/// to [`LintResults::skipped`] by a [`Baseline`].
+pub trait LintObserver: Sync + fmt::Debug {
+    /// Called before a linter runs against a target.
+    fn linter_started(&self, _source: &LintSource<'_>) {}
+
+    /// Called after a linter has run against a target, once all its messages have been observed.
+    fn linter_finished(&self, _source: &LintSource<'_>) {}
+
+    /// Called for each message reported by a linter, after level overrides have been applied.
+    fn message(&self, _source: &LintSource<'_>, _message: &LintMessage) {}
+
+    /// Called for each lint that was skipped, including messages that were suppressed.
+    fn skipped(&self, _source: &LintSource<'_>, _reason: &SkipReason<'_>) {}
+}
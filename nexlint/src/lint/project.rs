@@ -18,6 +18,16 @@ pub trait ProjectLinter: Linter {
         ctx: &ProjectContext<'l>,
         out: &mut LintFormatter<'l, '_>,
     ) -> Result<RunStatus<'l>>;
+
+    /// Returns true if this linter only needs to run if the changes being linted affect the
+    /// workspace.
+    ///
+    /// This is consulted if the lint engine is restricted to changes since a base revision. If no
+    /// workspace packages are affected by those changes, linters that return true are skipped. The
+    /// default implementation returns false, which means that the linter always runs.
+    fn affected_only(&self) -> bool {
+        false
+    }
 }
 
 /// Overall linter context for a project.
@@ -46,6 +56,11 @@ impl<'l> ProjectContext<'l> {
         self.core.package_graph()
     }
 
+    /// Returns the file classifier, reading Git attributes for the first time if necessary.
+    pub fn file_classifier(&self) -> Result<&'l FileClassifier> {
+        self.core.file_classifier()
+    }
+
     /// Returns the absolute path from the project root.
     pub fn full_path(&self, path: impl AsRef<Utf8Path>) -> Utf8PathBuf {
         self.core.project_root().join(path.as_ref())
@@ -1,8 +1,29 @@
 // Copyright (c) The nextest Contributors
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::{lint::LintContext, prelude::*, NexLintContext};
-use camino::Utf8Path;
+use crate::{
+    git::GitHash,
+    lint::{
+        cache::LintCache, fix::apply_fixes, level::Levels, suppress::Suppressions,
+        timings::TimingActivity, LintContext,
+    },
+    prelude::*,
+    NexLintContext,
+};
+use camino::{Utf8Path, Utf8PathBuf};
+use determinator::{Determinator, Utf8Paths0};
+use guppy::graph::{DependencyDirection, PackageMetadata, PackageSet};
+use once_cell::sync::OnceCell;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::{Serialize, Serializer};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    time::Instant,
+};
 
 /// Configuration for the lint engine.
 #[derive(Clone, Debug)]
@@ -12,7 +33,16 @@ pub struct LintEngineConfig<'cfg> {
     package_linters: &'cfg [&'cfg dyn PackageLinter],
     file_path_linters: &'cfg [&'cfg dyn FilePathLinter],
     content_linters: &'cfg [&'cfg dyn ContentLinter],
+    level_overrides: &'cfg [LevelOverride],
+    selection: Option<&'cfg LintSelection>,
+    observer: Option<&'cfg dyn LintObserver>,
     fail_fast: bool,
+    fail_fast_level: LintLevel,
+    threads: usize,
+    changed_since: Option<&'cfg str>,
+    paths: &'cfg [Utf8PathBuf],
+    packages: &'cfg [String],
+    cache_dir: Option<&'cfg Utf8Path>,
 }
 
 impl<'cfg> LintEngineConfig<'cfg> {
@@ -23,7 +53,16 @@ impl<'cfg> LintEngineConfig<'cfg> {
             package_linters: &[],
             file_path_linters: &[],
             content_linters: &[],
+            level_overrides: &[],
+            selection: None,
+            observer: None,
             fail_fast: false,
+            fail_fast_level: LintLevel::Error,
+            threads: 1,
+            changed_since: None,
+            paths: &[],
+            packages: &[],
+            cache_dir: None,
         }
     }
 
@@ -59,192 +98,1195 @@ impl<'cfg> LintEngineConfig<'cfg> {
         self
     }
 
+    /// Overrides the levels of messages from lints. If several overrides match a message, the last
+    /// one wins. These take precedence over levels in the project's configuration.
+    pub fn with_level_overrides(&mut self, level_overrides: &'cfg [LevelOverride]) -> &mut Self {
+        self.level_overrides = level_overrides;
+        self
+    }
+
+    /// Restricts the linters that are run to those selected by name. The engine fails to run if
+    /// any pattern in the selection doesn't match a configured linter.
+    pub fn with_selection(&mut self, selection: &'cfg LintSelection) -> &mut Self {
+        self.selection = Some(selection);
+        self
+    }
+
+    /// Sets an observer that's notified as linters run and report messages.
+    pub fn with_observer(&mut self, observer: &'cfg dyn LintObserver) -> &mut Self {
+        self.observer = Some(observer);
+        self
+    }
+
     pub fn fail_fast(&mut self, fail_fast: bool) -> &mut Self {
         self.fail_fast = fail_fast;
         self
     }
 
+    /// Sets the lowest level of message that stops the run if `fail_fast` is set.
+    ///
+    /// The default is `LintLevel::Error`, so warnings and informational messages don't stop the run.
+    pub fn fail_fast_level(&mut self, level: LintLevel) -> &mut Self {
+        self.fail_fast_level = level;
+        self
+    }
+
+    /// Sets the number of threads used to run package, file path and content linters.
+    ///
+    /// The default, 1, runs every linter on the current thread. 0 uses one thread per available
+    /// CPU. Messages are reported in the same order regardless of the number of threads.
+    pub fn threads(&mut self, threads: usize) -> &mut Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Restricts linting to changes since the merge base of `HEAD` and the given revision (for
+    /// example, `origin/main`).
+    ///
+    /// File path and content linters only run on changed files, including changes in the working
+    /// copy. Package linters only run on workspace packages that are affected by those changes, as
+    /// determined by the `determinator` crate. Project linters can opt into being skipped through
+    /// `ProjectLinter::affected_only`.
+    ///
+    /// To work out which packages are affected, the merge base is checked out into a scratch Git
+    /// worktree at `target/nexlint-scratch/tree` under the project root. The worktree is created
+    /// with `git worktree add` the first time, then reused and reset with `git reset --hard` on
+    /// later runs. If it's missing or broken, it's deleted, stale worktree metadata is cleared with
+    /// `git worktree prune`, and it's created again. Deleting the directory is always safe.
+    ///
+    /// If this is `None` (the default), the entire project is linted.
+    pub fn changed_since(&mut self, base_rev: Option<&'cfg str>) -> &mut Self {
+        self.changed_since = base_rev;
+        self
+    }
+
+    /// Restricts linting to the given paths, relative to the project root.
+    ///
+    /// File path and content linters only run on files under these paths. Package linters only run
+    /// on workspace packages that are under one of these paths, or that contain one of them.
+    /// Project linters aren't affected.
+    ///
+    /// If this is empty (the default), the entire project is linted.
+    pub fn with_paths(&mut self, paths: &'cfg [Utf8PathBuf]) -> &mut Self {
+        self.paths = paths;
+        self
+    }
+
+    /// Restricts linting to the workspace packages with the given names.
+    ///
+    /// Package linters only run on these packages, and file path and content linters only run on
+    /// files within them. Project linters aren't affected. The engine fails to run if any of these
+    /// aren't workspace packages.
+    ///
+    /// If this is empty (the default), every package is linted.
+    pub fn with_packages(&mut self, packages: &'cfg [String]) -> &mut Self {
+        self.packages = packages;
+        self
+    }
+
+    /// Caches content lint results in the given directory, such as
+    /// [`DEFAULT_CACHE_DIR`](crate::DEFAULT_CACHE_DIR) under the project root.
+    ///
+    /// Content linters that provide a [`ContentLinter::cache_fingerprint`] aren't run against files
+    /// they've already linted with the same path, contents and fingerprint. Instead, the messages
+    /// they reported last time are replayed. Level overrides and `nexlint-allow` directives are
+    /// applied to replayed messages as usual.
+    ///
+    /// If this is `None` (the default), no cache is used.
+    pub fn with_cache_dir(&mut self, cache_dir: Option<&'cfg Utf8Path>) -> &mut Self {
+        self.cache_dir = cache_dir;
+        self
+    }
+
     pub fn build(&self) -> LintEngine<'cfg> {
         LintEngine::new(self.clone())
     }
 }
 
+/// The maximum number of times fixes are applied before giving up on them converging.
+const MAX_FIX_PASSES: usize = 8;
+
+/// The linter name that unused `nexlint-allow` directives are reported under.
+const UNUSED_SUPPRESSION: &str = "unused-suppression";
+
 /// Executor for linters.
 #[derive(Debug)]
 pub struct LintEngine<'cfg> {
     config: LintEngineConfig<'cfg>,
     project_ctx: ProjectContext<'cfg>,
+    // Linters that were selected to run.
+    project_linters: Vec<&'cfg dyn ProjectLinter>,
+    package_linters: Vec<&'cfg dyn PackageLinter>,
+    file_path_linters: Vec<&'cfg dyn FilePathLinter>,
+    content_linters: Vec<&'cfg dyn ContentLinter>,
+    // Caches.
+    merge_base: OnceCell<GitHash>,
+    changed_files: OnceCell<Utf8Paths0>,
+    affected_packages: OnceCell<PackageSet<'cfg>>,
+    cache: OnceCell<LintCache>,
 }
 
 impl<'cfg> LintEngine<'cfg> {
     pub fn new(config: LintEngineConfig<'cfg>) -> Self {
         let project_ctx = ProjectContext::new(config.core);
+        let selected = |name: &str| {
+            config
+                .selection
+                .is_none_or(|selection| selection.is_selected(name))
+        };
         Self {
+            project_linters: select(config.project_linters, selected),
+            package_linters: select(config.package_linters, selected),
+            file_path_linters: select(config.file_path_linters, selected),
+            content_linters: select(config.content_linters, selected),
             config,
             project_ctx,
+            merge_base: OnceCell::new(),
+            changed_files: OnceCell::new(),
+            affected_packages: OnceCell::new(),
+            cache: OnceCell::new(),
         }
     }
 
     pub fn run(&self) -> Result<LintResults<'_>> {
-        let mut skipped = vec![];
-        let mut messages = vec![];
+        self.check_selection()?;
+        self.check_packages()?;
+        let mut results = LintResults::new();
+        let thread_pool = self.thread_pool()?;
+        let thread_pool = thread_pool.as_ref();
 
         // Run project linters.
-        if !self.config.project_linters.is_empty() {
-            for linter in self.config.project_linters {
+        if !self.project_linters.is_empty() {
+            let phase_start = Instant::now();
+            let mut stopped = false;
+            for linter in &self.project_linters {
                 let source = self.project_ctx.source(linter.name());
-                let mut formatter = LintFormatter::new(source, &mut messages);
-                match linter.run(&self.project_ctx, &mut formatter)? {
+                if linter.affected_only() && !self.workspace_affected()? {
+                    self.push_skipped(&mut results, source, SkipReason::Unaffected);
+                    continue;
+                }
+
+                self.notify(|observer| observer.linter_started(&source));
+                let first_message = results.messages.len();
+                let start = Instant::now();
+                let mut formatter = LintFormatter::new(source, &mut results.messages)
+                    .with_levels(self.levels().with_default_level(linter.default_level()));
+                let status = linter.run(&self.project_ctx, &mut formatter)?;
+                results
+                    .timings
+                    .record(TimingActivity::Linter(source), start);
+                match status {
                     RunStatus::Executed => {
-                        // Lint ran successfully.
+                        results.executed.push(source);
                     }
                     RunStatus::Skipped(reason) => {
-                        skipped.push((source, reason));
+                        self.push_skipped(&mut results, source, reason);
                     }
                 }
+                self.linter_finished(source, &results.messages[first_message..]);
 
-                if self.config.fail_fast && !messages.is_empty() {
+                if self.should_stop(&results) {
                     // At least one issue was found.
-                    return Ok(LintResults { skipped, messages });
+                    stopped = true;
+                    break;
                 }
             }
+
+            results
+                .timings
+                .record(TimingActivity::Phase(LinterKind::Project), phase_start);
+            if stopped {
+                return Ok(results);
+            }
         }
 
         // Run package linters.
-        if !self.config.package_linters.is_empty() {
+        if !self.package_linters.is_empty() {
+            let phase_start = Instant::now();
             let package_graph = self.project_ctx.package_graph()?;
+            let affected_packages = self.affected_packages()?;
+            let packages: Vec<_> = package_graph
+                .workspace()
+                .iter_by_path()
+                .filter(|&(workspace_path, metadata)| {
+                    self.package_in_scope(workspace_path, metadata)
+                })
+                .filter(|(_, metadata)| match affected_packages {
+                    Some(affected_packages) => affected_packages
+                        .contains(metadata.id())
+                        .expect("package IDs are all valid"),
+                    None => true,
+                })
+                .collect();
 
-            for (workspace_path, metadata) in package_graph.workspace().iter_by_path() {
-                let package_ctx = PackageContext::new(
-                    &self.project_ctx,
-                    package_graph,
-                    workspace_path,
-                    metadata,
-                )?;
-                for linter in self.config.package_linters {
-                    let source = package_ctx.source(linter.name());
-                    let mut formatter = LintFormatter::new(source, &mut messages);
-                    match linter.run(&package_ctx, &mut formatter)? {
-                        RunStatus::Executed => {
-                            // Lint ran successfully.
-                        }
-                        RunStatus::Skipped(reason) => {
-                            skipped.push((source, reason));
-                        }
-                    }
-
-                    if self.config.fail_fast && !messages.is_empty() {
-                        // At least one issue was found.
-                        return Ok(LintResults { skipped, messages });
-                    }
-                }
+            let stopped = self.run_units(
+                thread_pool,
+                &packages,
+                &mut results,
+                |&(workspace_path, metadata), results| {
+                    self.run_package(workspace_path, metadata, results)
+                },
+            )?;
+            results
+                .timings
+                .record(TimingActivity::Phase(LinterKind::Package), phase_start);
+            if stopped {
+                return Ok(results);
             }
         }
 
         // Run file path linters.
-        if !self.config.file_path_linters.is_empty() {
+        if !self.file_path_linters.is_empty() {
+            let phase_start = Instant::now();
             let file_list = self.file_list()?;
 
-            let file_ctxs = file_list.map(|path| FilePathContext::new(&self.project_ctx, path));
-
-            for file_ctx in file_ctxs {
-                for linter in self.config.file_path_linters {
-                    let source = file_ctx.source(linter.name());
-                    let mut formatter = LintFormatter::new(source, &mut messages);
-                    match linter.run(&file_ctx, &mut formatter)? {
-                        RunStatus::Executed => {
-                            // Lint ran successfully.
-                        }
-                        RunStatus::Skipped(reason) => {
-                            skipped.push((source, reason));
-                        }
-                    }
-
-                    if self.config.fail_fast && !messages.is_empty() {
-                        // At least one issue was found.
-                        return Ok(LintResults { skipped, messages });
-                    }
-                }
+            let stopped =
+                self.run_units(thread_pool, &file_list, &mut results, |path, results| {
+                    self.run_file_path(path, results)
+                })?;
+            results
+                .timings
+                .record(TimingActivity::Phase(LinterKind::FilePath), phase_start);
+            if stopped {
+                return Ok(results);
             }
         }
 
         // Run content linters.
-        if !self.config.content_linters.is_empty() {
+        if !self.content_linters.is_empty() {
+            let phase_start = Instant::now();
             let file_list = self.file_list()?;
 
-            // TODO: This should probably be a worker queue with a thread pool or something.
+            let stopped =
+                self.run_units(thread_pool, &file_list, &mut results, |path, results| {
+                    self.run_content(path, results)
+                })?;
+            results
+                .timings
+                .record(TimingActivity::Phase(LinterKind::Content), phase_start);
+            if stopped {
+                return Ok(results);
+            }
+        }
 
-            let file_ctxs = file_list.map(|path| FilePathContext::new(&self.project_ctx, path));
+        Ok(results)
+    }
 
-            for file_ctx in file_ctxs {
-                let linters_to_run = self
-                    .config
+    /// Runs linters and applies the fixes they suggest, re-running them until no more fixes are
+    /// suggested.
+    ///
+    /// The returned results are from the final run, so they only contain messages that couldn't
+    /// be fixed automatically.
+    pub fn fix(&self) -> Result<FixResults<'_>> {
+        let mut applied = 0;
+        let mut passes = 0;
+        loop {
+            let results = self.run()?;
+            let fixes: Vec<_> = results
+                .messages
+                .iter()
+                .filter_map(|(_, message)| message.fix())
+                .collect();
+            if fixes.is_empty() || passes == MAX_FIX_PASSES {
+                return Ok(FixResults {
+                    converged: fixes.is_empty(),
+                    results,
+                    applied,
+                });
+            }
+
+            let pass_applied = apply_fixes(self.project_ctx.project_root(), fixes)?;
+            if pass_applied == 0 {
+                // None of the suggested fixes could be applied, so re-running won't help.
+                return Ok(FixResults {
+                    results,
+                    applied,
+                    converged: false,
+                });
+            }
+            applied += pass_applied;
+            passes += 1;
+        }
+    }
+
+    // ---
+    // Helper methods
+    // ---
+
+    fn check_selection(&self) -> Result<()> {
+        let selection = match self.config.selection {
+            Some(selection) => selection,
+            None => return Ok(()),
+        };
+        let known: Vec<_> = self
+            .config
+            .project_linters
+            .iter()
+            .map(|linter| linter.name())
+            .chain(
+                self.config
+                    .package_linters
+                    .iter()
+                    .map(|linter| linter.name()),
+            )
+            .chain(
+                self.config
+                    .file_path_linters
+                    .iter()
+                    .map(|linter| linter.name()),
+            )
+            .chain(
+                self.config
                     .content_linters
                     .iter()
-                    .copied()
-                    .filter_map(|linter| match linter.pre_run(&file_ctx) {
-                        Ok(RunStatus::Executed) => Some(Ok(linter)),
-                        Ok(RunStatus::Skipped(reason)) => {
-                            let source = file_ctx.source(linter.name());
-                            skipped.push((source, reason));
-                            None
-                        }
-                        Err(err) => Some(Err(err)),
+                    .map(|linter| linter.name()),
+            )
+            .collect();
+        selection.check(&known)
+    }
+
+    fn check_packages(&self) -> Result<()> {
+        if self.config.packages.is_empty() {
+            return Ok(());
+        }
+        let (_, unknown): (Vec<_>, Vec<_>) = self
+            .config
+            .core
+            .partition_workspace_names(self.config.packages.iter().map(String::as_str))?;
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(SystemError::UnknownPackages(
+                unknown.into_iter().map(str::to_owned).collect(),
+            ))
+        }
+    }
+
+    fn file_list(&self) -> Result<Vec<&Utf8Path>> {
+        let git_cli = self.config.core.git_cli();
+        let files = match self.config.changed_since {
+            Some(base_rev) => self.changed_files.get_or_try_init(|| {
+                let merge_base = self.merge_base(base_rev)?;
+                // Deleted files can't be linted, so filter them out.
+                git_cli.files_changed_between(merge_base, None, Some("d"))
+            })?,
+            None => git_cli.tracked_files()?,
+        };
+
+        // Files must be within one of the selected packages, if any.
+        let package_paths = if self.config.packages.is_empty() {
+            None
+        } else {
+            let workspace = self.project_ctx.package_graph()?.workspace();
+            let package_paths: Vec<_> = workspace
+                .iter_by_path()
+                .filter(|(_, metadata)| {
+                    self.config
+                        .packages
+                        .iter()
+                        .any(|name| name == metadata.name())
+                })
+                .map(|(workspace_path, _)| workspace_path)
+                .collect();
+            Some(package_paths)
+        };
+
+        Ok(files
+            .iter()
+            .filter(|path| {
+                self.path_in_scope(path)
+                    && package_paths.as_ref().is_none_or(|package_paths| {
+                        package_paths
+                            .iter()
+                            .any(|package_path| path.starts_with(package_path))
                     })
-                    .collect::<Result<Vec<_>>>()?;
+            })
+            .collect())
+    }
 
-                if linters_to_run.is_empty() {
-                    // No linters to run for this file -- no point loading it.
-                    continue;
+    /// Returns true if the file at `path` is under one of the paths being linted.
+    fn path_in_scope(&self, path: &Utf8Path) -> bool {
+        self.config.paths.is_empty()
+            || self
+                .config
+                .paths
+                .iter()
+                .any(|scope| path.starts_with(scope))
+    }
+
+    /// Returns true if the workspace package at `workspace_path` is being linted.
+    fn package_in_scope(&self, workspace_path: &Utf8Path, metadata: PackageMetadata<'_>) -> bool {
+        let in_paths = self.config.paths.is_empty()
+            || self.config.paths.iter().any(|scope| {
+                workspace_path.starts_with(scope) || scope.starts_with(workspace_path)
+            });
+        let in_packages = self.config.packages.is_empty()
+            || self
+                .config
+                .packages
+                .iter()
+                .any(|name| name == metadata.name());
+        in_paths && in_packages
+    }
+
+    fn merge_base(&self, base_rev: &str) -> Result<&GitHash> {
+        self.merge_base
+            .get_or_try_init(|| self.config.core.git_cli().merge_base(base_rev))
+    }
+
+    /// Returns the set of packages affected by changes since the base revision, or `None` if
+    /// every package should be linted.
+    fn affected_packages(&self) -> Result<Option<&PackageSet<'cfg>>> {
+        let base_rev = match self.config.changed_since {
+            Some(base_rev) => base_rev,
+            None => return Ok(None),
+        };
+
+        self.affected_packages
+            .get_or_try_init(|| {
+                let git_cli = self.config.core.git_cli();
+                let merge_base = self.merge_base(base_rev)?;
+                let old_graph = git_cli.package_graph_at(merge_base)?;
+                let new_graph = self.project_ctx.package_graph()?;
+                // Unlike for the file list, deleted files are relevant here.
+                let changed_paths = git_cli.files_changed_between(merge_base, None, None)?;
+
+                let mut determinator = Determinator::new(&old_graph, new_graph);
+                determinator.add_changed_paths(&changed_paths);
+                Ok(determinator.compute().affected_set)
+            })
+            .map(Some)
+    }
+
+    /// Returns true if any workspace packages are affected by the changes being linted.
+    fn workspace_affected(&self) -> Result<bool> {
+        match self.affected_packages()? {
+            Some(affected_packages) => Ok(affected_packages
+                .packages(DependencyDirection::Forward)
+                .any(|package| package.in_workspace())),
+            None => Ok(true),
+        }
+    }
+
+    /// Returns the content lint cache, or `None` if caching is disabled.
+    fn cache(&self) -> Result<Option<&LintCache>> {
+        match self.config.cache_dir {
+            Some(cache_dir) => self
+                .cache
+                .get_or_try_init(|| LintCache::new(cache_dir))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn thread_pool(&self) -> Result<Option<ThreadPool>> {
+        if self.config.threads == 1 {
+            return Ok(None);
+        }
+        ThreadPoolBuilder::new()
+            .num_threads(self.config.threads)
+            .thread_name(|idx| format!("nexlint-{}", idx))
+            .build()
+            .map(Some)
+            .map_err(SystemError::ThreadPool)
+    }
+
+    fn levels(&self) -> Levels<'_> {
+        Levels::new(self.config.level_overrides, self.config.core.config())
+    }
+
+    fn notify(&self, f: impl FnOnce(&dyn LintObserver)) {
+        if let Some(observer) = self.config.observer {
+            f(observer);
+        }
+    }
+
+    /// Notifies the observer of the messages a linter reported, then that it has finished.
+    fn linter_finished(&self, source: LintSource<'_>, messages: &[(LintSource<'_>, LintMessage)]) {
+        self.notify(|observer| {
+            for (source, message) in messages {
+                observer.message(source, message);
+            }
+            observer.linter_finished(&source);
+        });
+    }
+
+    fn push_skipped<'l>(
+        &self,
+        results: &mut LintResults<'l>,
+        source: LintSource<'l>,
+        reason: SkipReason<'l>,
+    ) {
+        self.notify(|observer| observer.skipped(&source, &reason));
+        results.skipped.push((source, reason));
+    }
+
+    fn should_stop(&self, results: &LintResults<'_>) -> bool {
+        // At least one issue that fails the run was found.
+        self.config.fail_fast
+            && results
+                .messages
+                .iter()
+                .any(|(_, message)| message.level().is_at_least(self.config.fail_fast_level))
+    }
+
+    /// Runs `f` against every item in `items`, merging the results for each item in order.
+    ///
+    /// Returns true if the run was stopped early because of `fail_fast`.
+    fn run_units<'l, T, F>(
+        &self,
+        thread_pool: Option<&ThreadPool>,
+        items: &[T],
+        results: &mut LintResults<'l>,
+        f: F,
+    ) -> Result<bool>
+    where
+        T: Sync,
+        F: Fn(&T, &mut LintResults<'l>) -> Result<()> + Sync,
+    {
+        let thread_pool = match thread_pool {
+            Some(thread_pool) => thread_pool,
+            None => {
+                for item in items {
+                    f(item, results)?;
+                    if self.should_stop(results) {
+                        return Ok(true);
+                    }
                 }
+                return Ok(false);
+            }
+        };
+
+        let cancelled = AtomicBool::new(false);
+        let (sender, receiver) = mpsc::channel();
 
-                // Load up the content for this file.
-                let content_ctx = match file_ctx.load()? {
-                    Some(content_ctx) => content_ctx,
-                    None => {
-                        // This file is missing -- can't run content linters on it.
-                        continue;
+        thread_pool.in_place_scope(|scope| {
+            for (idx, item) in items.iter().enumerate() {
+                let sender = sender.clone();
+                let (f, cancelled) = (&f, &cancelled);
+                scope.spawn(move |_| {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return;
                     }
-                };
+                    let mut unit_results = LintResults::new();
+                    let res = f(item, &mut unit_results).map(|()| unit_results);
+                    // The receiver is dropped if the run stops early, so ignore send errors.
+                    let _ = sender.send((idx, res));
+                });
+            }
+            drop(sender);
 
-                for linter in linters_to_run {
-                    let source = content_ctx.source(linter.name());
-                    let mut formatter = LintFormatter::new(source, &mut messages);
+            // Units can finish in any order, so buffer them up and merge them in the order they
+            // were submitted in. This keeps both messages and fail-fast behavior deterministic.
+            let mut pending = BTreeMap::new();
+            let mut next_idx = 0;
+            for (idx, res) in receiver {
+                pending.insert(idx, res);
+                while let Some(res) = pending.remove(&next_idx) {
+                    next_idx += 1;
+                    let stop = match res {
+                        Ok(unit_results) => {
+                            results.extend(unit_results);
+                            self.should_stop(results)
+                        }
+                        Err(err) => {
+                            cancelled.store(true, Ordering::Relaxed);
+                            return Err(err);
+                        }
+                    };
+                    if stop {
+                        cancelled.store(true, Ordering::Relaxed);
+                        return Ok(true);
+                    }
+                }
+            }
+
+            Ok(false)
+        })
+    }
+
+    fn run_package<'l>(
+        &'l self,
+        workspace_path: &'l Utf8Path,
+        metadata: PackageMetadata<'l>,
+        results: &mut LintResults<'l>,
+    ) -> Result<()> {
+        let package_graph = self.project_ctx.package_graph()?;
+        let package_ctx =
+            PackageContext::new(&self.project_ctx, package_graph, workspace_path, metadata)?;
+        for linter in &self.package_linters {
+            let source = package_ctx.source(linter.name());
+            self.notify(|observer| observer.linter_started(&source));
+            let first_message = results.messages.len();
+            let start = Instant::now();
+            let mut formatter = LintFormatter::new(source, &mut results.messages)
+                .with_levels(self.levels().with_default_level(linter.default_level()));
+            let status = linter.run(&package_ctx, &mut formatter)?;
+            results
+                .timings
+                .record(TimingActivity::Linter(source), start);
+            match status {
+                RunStatus::Executed => {
+                    results.executed.push(source);
+                }
+                RunStatus::Skipped(reason) => {
+                    self.push_skipped(results, source, reason);
+                }
+            }
+            self.linter_finished(source, &results.messages[first_message..]);
+
+            if self.should_stop(results) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn run_file_path<'l>(
+        &'l self,
+        path: &'l Utf8Path,
+        results: &mut LintResults<'l>,
+    ) -> Result<()> {
+        let file_ctx = FilePathContext::new(&self.project_ctx, path);
+        for linter in &self.file_path_linters {
+            let source = file_ctx.source(linter.name());
+            self.notify(|observer| observer.linter_started(&source));
+            let first_message = results.messages.len();
+            let start = Instant::now();
+            let mut formatter = LintFormatter::new(source, &mut results.messages)
+                .with_levels(self.levels().with_default_level(linter.default_level()));
+            let status = linter.run(&file_ctx, &mut formatter)?;
+            results
+                .timings
+                .record(TimingActivity::Linter(source), start);
+            match status {
+                RunStatus::Executed => {
+                    results.executed.push(source);
+                }
+                RunStatus::Skipped(reason) => {
+                    self.push_skipped(results, source, reason);
+                }
+            }
+            self.linter_finished(source, &results.messages[first_message..]);
+
+            if self.should_stop(results) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn run_content<'l>(&'l self, path: &'l Utf8Path, results: &mut LintResults<'l>) -> Result<()> {
+        let file_ctx = FilePathContext::new(&self.project_ctx, path);
+        let linters_to_run = self
+            .content_linters
+            .iter()
+            .copied()
+            .filter_map(|linter| {
+                let start = Instant::now();
+                let status = linter.pre_run(&file_ctx);
+                // Count pre_run towards the content linter, like the run that follows it.
+                let content_source = LintSource::new(linter.name(), LintKind::Content(path));
+                results
+                    .timings
+                    .record(TimingActivity::Linter(content_source), start);
+                match status {
+                    Ok(PreRunStatus::Executed(state)) => Some(Ok((linter, state))),
+                    Ok(PreRunStatus::Skipped(reason)) => {
+                        let source = file_ctx.source(linter.name());
+                        self.push_skipped(results, source, reason);
+                        None
+                    }
+                    Err(err) => Some(Err(err)),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if linters_to_run.is_empty() {
+            // No linters to run for this file -- no point loading it.
+            return Ok(());
+        }
+
+        // Load up the content for this file.
+        let start = Instant::now();
+        let content_ctx = file_ctx.load()?;
+        results
+            .timings
+            .record(TimingActivity::FileLoad(path), start);
+        let mut content_ctx = match content_ctx {
+            Some(content_ctx) => content_ctx,
+            None => {
+                // This file is missing -- can't run content linters on it.
+                return Ok(());
+            }
+        };
+
+        let mut suppressions = Suppressions::parse(content_ctx.content().unwrap_or_default());
+        let mut executed = vec![];
+        let cache = self.cache()?;
+        let levels = self.levels();
 
+        for (linter, state) in linters_to_run {
+            content_ctx.set_pre_run_state(state);
+            let source = content_ctx.source(linter.name());
+            self.notify(|observer| observer.linter_started(&source));
+            let first_message = results.messages.len();
+            let start = Instant::now();
+            let cache_key = cache.and_then(|cache| cache.key(linter, &content_ctx));
+
+            // Levels are applied below rather than by the formatter, so that cached messages are
+            // stored at the levels linters reported them at.
+            let cached = cache.zip(cache_key).and_then(|(cache, key)| cache.get(key));
+            let messages = match cached {
+                Some(cached) => {
+                    executed.push(linter.name());
+                    results.executed.push(source);
+                    cached
+                        .into_iter()
+                        .map(|message| (source, message))
+                        .collect()
+                }
+                None => {
+                    let mut messages = vec![];
+                    let mut formatter = LintFormatter::new(source, &mut messages);
                     match linter.run(&content_ctx, &mut formatter)? {
                         RunStatus::Executed => {
-                            // Yay! Lint ran successfully.
+                            executed.push(linter.name());
+                            results.executed.push(source);
+                            if let (Some(cache), Some(key)) = (cache, cache_key) {
+                                // Messages for other files can't be replayed from this one's entry.
+                                if messages
+                                    .iter()
+                                    .all(|(other, _)| other.kind() == source.kind())
+                                {
+                                    let to_cache = messages
+                                        .iter()
+                                        .map(|(_, message)| message.clone())
+                                        .collect();
+                                    cache.put(key, to_cache)?;
+                                }
+                            }
                         }
                         RunStatus::Skipped(reason) => {
-                            skipped.push((source, reason));
+                            self.push_skipped(results, source, reason);
                         }
                     }
+                    messages
+                }
+            };
+            results
+                .timings
+                .record(TimingActivity::Linter(source), start);
 
-                    if self.config.fail_fast && !messages.is_empty() {
-                        // At least one issue was found.
-                        return Ok(LintResults { skipped, messages });
+            let linter_levels = levels.with_default_level(linter.default_level());
+            for (source, message) in messages {
+                // Match suppressions before dropping allowed messages, so that directives for
+                // allowed lints aren't reported as unused.
+                let suppressed = suppressions.suppresses(source.name(), &message);
+                let mut message = match linter_levels.apply(&source, message) {
+                    Some(message) => message,
+                    None => continue,
+                };
+                if let Some(content) = content_ctx.content() {
+                    // Linters may write messages for other files with write_kind.
+                    if source.kind() == content_ctx.kind() {
+                        message.attach_source(content);
                     }
                 }
+                if suppressed {
+                    self.push_skipped(results, source, SkipReason::Suppressed(Box::new(message)));
+                } else {
+                    results.messages.push((source, message));
+                }
             }
-        }
+            self.linter_finished(source, &results.messages[first_message..]);
 
-        Ok(LintResults { skipped, messages })
-    }
+            if self.should_stop(results) {
+                break;
+            }
+        }
 
-    // ---
-    // Helper methods
-    // ---
+        let source = content_ctx.source(UNUSED_SUPPRESSION);
+        let first_message = results.messages.len();
+        results.messages.extend(
+            suppressions
+                .unused_messages(
+                    |name| {
+                        self.config
+                            .content_linters
+                            .iter()
+                            .any(|linter| linter.name() == name)
+                    },
+                    |name| executed.contains(&name),
+                )
+                .into_iter()
+                .filter_map(|mut message| {
+                    if let Some(content) = content_ctx.content() {
+                        message.attach_source(content);
+                    }
+                    Some((source, levels.apply(&source, message)?))
+                }),
+        );
+        for (source, message) in &results.messages[first_message..] {
+            self.notify(|observer| observer.message(source, message));
+        }
 
-    fn file_list(&self) -> Result<impl Iterator<Item = &'cfg Utf8Path> + 'cfg> {
-        let git_cli = self.config.core.git_cli();
-        let tracked_files = git_cli.tracked_files()?;
-        Ok(tracked_files.iter())
+        Ok(())
     }
 }
 
-#[derive(Debug)]
+/// Returns the linters in `linters` with names that are `selected`.
+fn select<'a, L: ?Sized + Linter>(
+    linters: &[&'a L],
+    selected: impl Fn(&str) -> bool,
+) -> Vec<&'a L> {
+    linters
+        .iter()
+        .copied()
+        .filter(|linter| selected(linter.name()))
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
 #[non_exhaustive]
 pub struct LintResults<'l> {
+    /// Every linter and target that a linter ran against, whether or not it produced messages.
+    #[serde(skip)]
+    pub executed: Vec<LintSource<'l>>,
+    #[serde(serialize_with = "serialize_skipped")]
     pub skipped: Vec<(LintSource<'l>, SkipReason<'l>)>,
+    #[serde(serialize_with = "serialize_messages")]
     pub messages: Vec<(LintSource<'l>, LintMessage)>,
+    /// How long linters, phases of the run and loading files took.
+    #[serde(skip)]
+    pub timings: LintTimings<'l>,
+}
+
+impl<'l> LintResults<'l> {
+    pub(crate) fn new() -> Self {
+        Self {
+            executed: vec![],
+            skipped: vec![],
+            messages: vec![],
+            timings: LintTimings::default(),
+        }
+    }
+
+    fn extend(&mut self, other: LintResults<'l>) {
+        self.executed.extend(other.executed);
+        self.skipped.extend(other.skipped);
+        self.messages.extend(other.messages);
+        self.timings.extend(other.timings);
+    }
+}
+
+/// A lint message along with its source, serialized as a single flat record.
+#[derive(Serialize)]
+pub(crate) struct MessageRecord<'a, 'l> {
+    #[serde(flatten)]
+    pub(crate) source: &'a LintSource<'l>,
+    #[serde(flatten)]
+    pub(crate) message: &'a LintMessage,
+}
+
+/// A skipped lint along with the reason it was skipped, serialized as a single flat record.
+#[derive(Serialize)]
+pub(crate) struct SkipRecord<'a, 'l> {
+    #[serde(flatten)]
+    pub(crate) source: &'a LintSource<'l>,
+    #[serde(flatten)]
+    pub(crate) reason: &'a SkipReason<'l>,
+}
+
+fn serialize_skipped<S: Serializer>(
+    skipped: &[(LintSource<'_>, SkipReason<'_>)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        skipped
+            .iter()
+            .map(|(source, reason)| SkipRecord { source, reason }),
+    )
+}
+
+fn serialize_messages<S: Serializer>(
+    messages: &[(LintSource<'_>, LintMessage)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        messages
+            .iter()
+            .map(|(source, message)| MessageRecord { source, message }),
+    )
+}
+
+/// Results of a `LintEngine::fix` run.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct FixResults<'l> {
+    /// Results of the final lint run, after fixes were applied.
+    pub results: LintResults<'l>,
+    /// The number of fixes that were applied.
+    pub applied: usize,
+    /// False if linters were still suggesting fixes after the maximum number of passes, or if
+    /// none of the suggested fixes could be applied.
+    pub converged: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestRepo, TodoLinter};
+
+    /// Returns the path and text of each message, in the order they were reported.
+    fn messages<'a>(results: &'a LintResults<'_>) -> Vec<(String, &'a str)> {
+        results
+            .messages
+            .iter()
+            .map(|(source, message)| (source.kind().file_path().to_string(), message.message()))
+            .collect()
+    }
+
+    fn many_files() -> TestRepo {
+        let files: Vec<_> = (0..40)
+            .map(|idx| {
+                let contents = match idx % 3 {
+                    0 => "nothing to see here\n",
+                    1 => "TODO\n",
+                    _ => "TODO once\nTODO twice\n",
+                };
+                (format!("file-{:02}.txt", idx), contents)
+            })
+            .collect();
+        let files: Vec<_> = files
+            .iter()
+            .map(|(path, contents)| (path.as_str(), *contents))
+            .collect();
+        TestRepo::new(&files)
+    }
+
+    #[test]
+    fn parallel_results_are_in_order() {
+        let repo = many_files();
+        let ctx = repo.context();
+        let todo = TodoLinter::new("todo");
+        let content_linters: &[&dyn ContentLinter] = &[&todo];
+
+        let run = |threads| {
+            let engine = LintEngineConfig::new(&ctx)
+                .with_content_linters(content_linters)
+                .threads(threads)
+                .build();
+            let results = engine.run().expect("lint run succeeded");
+            let messages: Vec<_> = messages(&results)
+                .into_iter()
+                .map(|(path, message)| (path, message.to_owned()))
+                .collect();
+            messages
+        };
+
+        let serial = run(1);
+        assert_eq!(serial.len(), 39, "13 files with one TODO, and 13 with two");
+        let mut sorted = serial.clone();
+        sorted.sort();
+        assert_eq!(serial, sorted, "messages are in file order");
+
+        // 0 means one thread per CPU.
+        for threads in [0, 2, 8] {
+            assert_eq!(
+                run(threads),
+                serial,
+                "same messages with {} threads",
+                threads
+            );
+        }
+    }
+
+    #[test]
+    fn parallel_fail_fast_stops_at_first_failure() {
+        let repo = many_files();
+        let ctx = repo.context();
+        let todo = TodoLinter::new("todo");
+        let content_linters: &[&dyn ContentLinter] = &[&todo];
+
+        for threads in [1, 4] {
+            let engine = LintEngineConfig::new(&ctx)
+                .with_content_linters(content_linters)
+                .threads(threads)
+                .fail_fast(true)
+                .build();
+            let results = engine.run().expect("lint run succeeded");
+            assert_eq!(
+                messages(&results),
+                vec![("file-01.txt".to_owned(), "found TODO")],
+                "only the first failing file is reported with {} threads",
+                threads,
+            );
+        }
+
+        // Warnings don't stop the run unless the threshold is lowered.
+        let warn = TodoLinter::new("warn").with_default_level(LintLevel::Warning);
+        let content_linters: &[&dyn ContentLinter] = &[&warn];
+        for threads in [1, 4] {
+            let engine = LintEngineConfig::new(&ctx)
+                .with_content_linters(content_linters)
+                .threads(threads)
+                .fail_fast(true)
+                .build();
+            let results = engine.run().expect("lint run succeeded");
+            assert_eq!(messages(&results).len(), 39, "{} threads", threads);
+
+            let engine = LintEngineConfig::new(&ctx)
+                .with_content_linters(content_linters)
+                .threads(threads)
+                .fail_fast(true)
+                .fail_fast_level(LintLevel::Warning)
+                .build();
+            let results = engine.run().expect("lint run succeeded");
+            assert_eq!(
+                messages(&results),
+                vec![("file-01.txt".to_owned(), "found TODO")],
+                "warnings stop the run with {} threads",
+                threads,
+            );
+        }
+    }
+
+    /// A content linter that always suggests inserting another line at the start of the file.
+    #[derive(Debug)]
+    struct NeverSatisfied;
+
+    impl Linter for NeverSatisfied {
+        fn name(&self) -> &'static str {
+            "never-satisfied"
+        }
+    }
+
+    impl ContentLinter for NeverSatisfied {
+        fn run<'l>(
+            &self,
+            ctx: &ContentContext<'l>,
+            out: &mut LintFormatter<'l, '_>,
+        ) -> Result<RunStatus<'l>> {
+            let path = ctx.file_ctx().file_path();
+            let fix = LintFix::new("add a line", vec![TextEdit::insert(path, 0, "more\n")]);
+            out.write_message(LintMessage::new(LintLevel::Error, "not enough lines").with_fix(fix));
+            Ok(RunStatus::Executed)
+        }
+    }
+
+    #[test]
+    fn fix_converges() {
+        let repo = TestRepo::new(&[("a.txt", "TODO: a TODO\n"), ("b.txt", "TODO\n")]);
+        let ctx = repo.context();
+        let todo = TodoLinter::new("todo");
+        let content_linters: &[&dyn ContentLinter] = &[&todo];
+        let engine = LintEngineConfig::new(&ctx)
+            .with_content_linters(content_linters)
+            .build();
+
+        let fix_results = engine.fix().expect("fix run succeeded");
+        assert!(fix_results.converged);
+        assert_eq!(fix_results.applied, 3);
+        assert!(fix_results.results.messages.is_empty());
+        assert_eq!(repo.read("a.txt"), ": a \n");
+        assert_eq!(repo.read("b.txt"), "\n");
+    }
+
+    #[test]
+    fn fix_gives_up_after_max_passes() {
+        let repo = TestRepo::new(&[("a.txt", "")]);
+        let ctx = repo.context();
+        let content_linters: &[&dyn ContentLinter] = &[&NeverSatisfied];
+        let engine = LintEngineConfig::new(&ctx)
+            .with_content_linters(content_linters)
+            .build();
+
+        let fix_results = engine.fix().expect("fix run succeeded");
+        assert!(!fix_results.converged);
+        assert_eq!(fix_results.applied, MAX_FIX_PASSES);
+        assert_eq!(repo.read("a.txt"), "more\n".repeat(MAX_FIX_PASSES));
+        // The results are from the final run, which still suggests a fix.
+        assert_eq!(fix_results.results.messages.len(), 1);
+    }
+
+    #[test]
+    fn skipped_content_linters_dont_run() {
+        let repo = TestRepo::new(&[("a.txt", "TODO\n")]);
+        let ctx = repo.context();
+        let (first, second) = (
+            TodoLinter::new("first-todo"),
+            TodoLinter::new("second-todo"),
+        );
+        let content_linters: &[&dyn ContentLinter] = &[&first, &second];
+
+        let selection = LintSelection::new()
+            .with_skip_lints(["first-*"])
+            .expect("valid glob");
+        let engine = LintEngineConfig::new(&ctx)
+            .with_content_linters(content_linters)
+            .with_selection(&selection)
+            .build();
+        let results = engine.run().expect("lint run succeeded");
+
+        assert_eq!(first.runs(), 0);
+        assert_eq!(second.runs(), 1);
+        let names: Vec<_> = results
+            .messages
+            .iter()
+            .map(|(source, _)| source.name())
+            .collect();
+        assert_eq!(names, ["second-todo"]);
+    }
+
+    #[test]
+    fn allowed_lints_use_suppressions() {
+        let repo = TestRepo::new(&[("a.txt", "# nexlint-allow(todo)\nTODO\n")]);
+        let ctx = repo.context();
+        let todo = TodoLinter::new("todo");
+        let content_linters: &[&dyn ContentLinter] = &[&todo];
+        let allow = [LevelOverride::new("todo", OverrideLevel::Allow)];
+
+        let engine = LintEngineConfig::new(&ctx)
+            .with_content_linters(content_linters)
+            .with_level_overrides(&allow)
+            .build();
+        let results = engine.run().expect("lint run succeeded");
+
+        // The directive isn't reported as unused, and the allowed message isn't reported at all.
+        assert_eq!(messages(&results), []);
+        assert!(results.skipped.is_empty(), "{:?}", results.skipped);
+    }
+
+    #[test]
+    fn default_levels() {
+        let repo = TestRepo::new(&[("a.txt", "TODO\n")]);
+        let ctx = repo.context();
+        let todo = TodoLinter::new("todo").with_default_level(LintLevel::Warning);
+        let explicit = TodoLinter::new("explicit").with_level(LintLevel::Warning);
+        let content_linters: &[&dyn ContentLinter] = &[&todo, &explicit];
+
+        let run = |level_overrides| {
+            let engine = LintEngineConfig::new(&ctx)
+                .with_content_linters(content_linters)
+                .with_level_overrides(level_overrides)
+                .build();
+            let results = engine.run().expect("lint run succeeded");
+            let levels: Vec<_> = results
+                .messages
+                .iter()
+                .map(|(_, message)| message.level())
+                .collect();
+            levels
+        };
+
+        // Messages written at the default level are reported at the linter's default level, while
+        // explicit levels are kept as they are, even though the default level of `explicit` is
+        // `Error`.
+        assert_eq!(run(&[]), [LintLevel::Warning, LintLevel::Warning]);
+        let deny = [LevelOverride::new("todo", OverrideLevel::Deny)];
+        assert_eq!(run(&deny), [LintLevel::Error, LintLevel::Warning]);
+    }
 }
@@ -0,0 +1,90 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Selection of the linters to run by name.
+
+use crate::prelude::*;
+use globset::{Glob, GlobMatcher};
+
+/// Selects which linters a [`LintEngine`] runs, by matching globs against [`Linter::name`].
+///
+/// By default, every linter is selected. If any lints are given with `with_lints`, only linters
+/// matching at least one of them are run. Linters matching any of the skipped lints are never run.
+#[derive(Clone, Debug, Default)]
+pub struct LintSelection {
+    lints: Vec<NamePattern>,
+    skip_lints: Vec<NamePattern>,
+}
+
+impl LintSelection {
+    /// Creates a selection that runs every linter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only runs linters with names matching any of the given globs, such as `crate-*`.
+    pub fn with_lints<'a>(mut self, patterns: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        self.lints = parse_patterns(patterns)?;
+        Ok(self)
+    }
+
+    /// Doesn't run linters with names matching any of the given globs.
+    pub fn with_skip_lints<'a>(
+        mut self,
+        patterns: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self> {
+        self.skip_lints = parse_patterns(patterns)?;
+        Ok(self)
+    }
+
+    /// Returns true if the linter with the given name is selected.
+    pub fn is_selected(&self, name: &str) -> bool {
+        let included = self.lints.is_empty()
+            || self
+                .lints
+                .iter()
+                .any(|pattern| pattern.matcher.is_match(name));
+        included
+            && !self
+                .skip_lints
+                .iter()
+                .any(|pattern| pattern.matcher.is_match(name))
+    }
+
+    /// Checks that every pattern in this selection matches at least one of the known linters.
+    pub(super) fn check(&self, known: &[&'static str]) -> Result<()> {
+        for pattern in self.lints.iter().chain(&self.skip_lints) {
+            if !known.iter().any(|name| pattern.matcher.is_match(name)) {
+                let mut known = known.to_vec();
+                known.sort_unstable();
+                known.dedup();
+                return Err(SystemError::UnknownLint {
+                    pattern: pattern.pattern.clone(),
+                    known,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct NamePattern {
+    pattern: String,
+    matcher: GlobMatcher,
+}
+
+fn parse_patterns<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Result<Vec<NamePattern>> {
+    patterns
+        .into_iter()
+        .map(|pattern| {
+            let glob = Glob::new(pattern).map_err(|err| {
+                SystemError::glob(format!("parsing lint name glob '{}'", pattern), err)
+            })?;
+            Ok(NamePattern {
+                pattern: pattern.to_owned(),
+                matcher: glob.compile_matcher(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,206 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Inline suppression directives for content lints.
+//!
+//! A comment consisting of `nexlint-allow(name)` suppresses messages from the named linters on the
+//! line that follows it, and `nexlint-allow-file(name)` suppresses them for the whole file.
+//! Several linters may be listed, separated by commas. Any text after the closing parenthesis is
+//! ignored, so it can be used to explain why the lint is suppressed.
+//!
+//! Directives that don't suppress anything, or that name a linter that doesn't exist, are reported
+//! as warnings.
+
+use crate::prelude::*;
+
+const NEXT_LINE_DIRECTIVE: &str = "nexlint-allow(";
+const FILE_DIRECTIVE: &str = "nexlint-allow-file(";
+
+/// Comment markers that may introduce a directive. The directive must follow the marker directly
+/// (after whitespace), so that doc comments and prose that mention directives aren't picked up.
+const COMMENT_MARKERS: &[&str] = &["//", "#", "/*", "<!--", "--", ";"];
+
+/// The suppression directives found in a file.
+#[derive(Debug)]
+pub(super) struct Suppressions {
+    directives: Vec<Directive>,
+}
+
+#[derive(Debug)]
+struct Directive {
+    name: String,
+    scope: Scope,
+    span: LintSpan,
+    used: bool,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Scope {
+    File,
+    /// The 1-based line the directive applies to.
+    Line(usize),
+}
+
+impl Suppressions {
+    /// Parses suppression directives out of the given file content.
+    pub(super) fn parse(content: &str) -> Self {
+        let mut directives = vec![];
+        if !content.contains("nexlint-allow") {
+            // Fast path: most files don't have any directives.
+            return Self { directives };
+        }
+
+        let mut offset = 0;
+        for (line_idx, line) in content.split_inclusive('\n').enumerate() {
+            let line_start = offset;
+            offset += line.len();
+
+            let Some((directive_start, scope, rest)) = parse_line(line, line_idx + 1) else {
+                continue;
+            };
+            let Some(close) = rest.find(')') else {
+                continue;
+            };
+            let directive_end = line.len() - rest.len() + close + 1;
+            let span = LintSpan::from_byte_range(
+                content,
+                (line_start + directive_start)..(line_start + directive_end),
+            );
+
+            directives.extend(
+                rest[..close]
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(|name| Directive {
+                        name: name.to_owned(),
+                        scope,
+                        span: span.clone(),
+                        used: false,
+                    }),
+            );
+        }
+
+        Self { directives }
+    }
+
+    /// Returns true if the given message from the named linter is suppressed, and marks the
+    /// directives responsible as used.
+    ///
+    /// Messages without a span can only be suppressed by file-level directives.
+    pub(super) fn suppresses(&mut self, name: &str, message: &LintMessage) -> bool {
+        let line = message.span().map(|span| span.start().line);
+        let mut suppressed = false;
+        for directive in &mut self.directives {
+            if directive.name != name {
+                continue;
+            }
+            let applies = match directive.scope {
+                Scope::File => true,
+                Scope::Line(target) => line == Some(target),
+            };
+            if applies {
+                directive.used = true;
+                suppressed = true;
+            }
+        }
+        suppressed
+    }
+
+    /// Returns messages for directives that didn't suppress anything.
+    ///
+    /// Directives naming a linter that `is_known` returns false for are always reported, since
+    /// they can never suppress anything. Other unused directives are only reported for linters
+    /// for which `was_run` returns true.
+    pub(super) fn unused_messages(
+        &self,
+        is_known: impl Fn(&str) -> bool,
+        was_run: impl Fn(&str) -> bool,
+    ) -> Vec<LintMessage> {
+        self.directives
+            .iter()
+            .filter(|directive| !directive.used)
+            .filter_map(|directive| {
+                let message = if !is_known(&directive.name) {
+                    LintMessage::new(
+                        LintLevel::Warning,
+                        format!("suppression for unknown linter '{}'", directive.name),
+                    )
+                    .with_help("check the linter's name, or remove it from the directive")
+                } else if was_run(&directive.name) {
+                    LintMessage::new(
+                        LintLevel::Warning,
+                        format!("unused suppression for linter '{}'", directive.name),
+                    )
+                    .with_help("remove the linter from the directive")
+                } else {
+                    return None;
+                };
+                Some(message.with_span(directive.span.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Returns the byte offset of the directive within the line, its scope, and the text following the
+/// directive's opening parenthesis.
+fn parse_line(line: &str, line_number: usize) -> Option<(usize, Scope, &str)> {
+    let trimmed = line.trim_start();
+    let after_marker = COMMENT_MARKERS
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker))?
+        .trim_start();
+    let directive_start = line.len() - after_marker.len();
+
+    if let Some(rest) = after_marker.strip_prefix(FILE_DIRECTIVE) {
+        Some((directive_start, Scope::File, rest))
+    } else {
+        after_marker
+            .strip_prefix(NEXT_LINE_DIRECTIVE)
+            .map(|rest| (directive_start, Scope::Line(line_number + 1), rest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unused(suppressions: &Suppressions) -> Vec<String> {
+        suppressions
+            .unused_messages(|name| name != "unknown", |name| name != "not-run")
+            .iter()
+            .map(|message| format!("{}: {}", message.span().unwrap(), message.message()))
+            .collect()
+    }
+
+    #[test]
+    fn suppress_and_report_unused() {
+        let content = "\
+            // nexlint-allow(used, unused) because reasons\n\
+            line 2\n\
+            # nexlint-allow-file(not-run)\n\
+            /// nexlint-allow(doc-comments-are-ignored)\n\
+            <!-- nexlint-allow(unknown) -->\n";
+        let mut suppressions = Suppressions::parse(content);
+
+        let at_line = |line| {
+            LintMessage::new(LintLevel::Error, "message").with_span(LintSpan::new(
+                LineColumn::new(line, 1),
+                LineColumn::new(line, 2),
+            ))
+        };
+        assert!(suppressions.suppresses("used", &at_line(2)));
+        assert!(!suppressions.suppresses("used", &at_line(3)));
+        assert!(!suppressions.suppresses("other", &at_line(2)));
+        // Messages without a span can't be suppressed by line directives.
+        assert!(!suppressions.suppresses("unused", &LintMessage::new(LintLevel::Error, "message")));
+
+        assert_eq!(
+            unused(&suppressions),
+            [
+                "1:4: unused suppression for linter 'unused'",
+                "5:6: suppression for unknown linter 'unknown'",
+            ]
+        );
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Wall-clock timings recorded while linters run.
+
+use crate::prelude::*;
+use camino::Utf8Path;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// The timings recorded during a lint run, as a list of events.
+///
+/// Events are recorded on the thread the work happened on, so events on different threads can
+/// overlap. Totals therefore add up time across threads, and can be more than the wall time of
+/// the run.
+#[derive(Clone, Debug, Default)]
+pub struct LintTimings<'l> {
+    events: Vec<TimingEvent<'l>>,
+}
+
+impl<'l> LintTimings<'l> {
+    /// Returns every recorded event, in the order they finished on each thread.
+    pub fn events(&self) -> &[TimingEvent<'l>] {
+        &self.events
+    }
+
+    /// Returns the total time spent in each linter, from slowest to fastest.
+    ///
+    /// For content linters, this includes time spent in `pre_run` and looking up cached results.
+    pub fn by_linter(&self) -> Vec<LinterTiming> {
+        let mut totals: HashMap<(&'static str, LinterKind), LinterTiming> = HashMap::new();
+        for event in &self.events {
+            if let TimingActivity::Linter(source) = event.activity {
+                let kind = linter_kind(source.kind());
+                let timing = totals
+                    .entry((source.name(), kind))
+                    .or_insert_with(|| LinterTiming {
+                        name: source.name(),
+                        kind,
+                        total: Duration::ZERO,
+                        calls: 0,
+                    });
+                timing.total += event.duration;
+                timing.calls += 1;
+            }
+        }
+
+        let mut totals: Vec<_> = totals.into_values().collect();
+        totals.sort_by(|a, b| b.total.cmp(&a.total).then(a.name.cmp(b.name)));
+        totals
+    }
+
+    /// Returns the wall time of each phase that ran, in the order they ran in.
+    pub fn by_phase(&self) -> Vec<(LinterKind, Duration)> {
+        self.events
+            .iter()
+            .filter_map(|event| match event.activity {
+                TimingActivity::Phase(kind) => Some((kind, event.duration)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the total time spent loading files for content linters.
+    pub fn file_load(&self) -> Duration {
+        self.events
+            .iter()
+            .filter(|event| matches!(event.activity, TimingActivity::FileLoad(_)))
+            .map(|event| event.duration)
+            .sum()
+    }
+
+    /// Records an event for `activity` that started at `start` and finishes now.
+    pub(super) fn record(&mut self, activity: TimingActivity<'l>, start: Instant) {
+        self.events.push(TimingEvent {
+            activity,
+            start,
+            duration: start.elapsed(),
+            thread: rayon::current_thread_index().map_or(0, |idx| idx + 1),
+        });
+    }
+
+    pub(super) fn extend(&mut self, other: LintTimings<'l>) {
+        self.events.extend(other.events);
+    }
+}
+
+/// Something that was timed during a lint run.
+#[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
+pub enum TimingActivity<'l> {
+    /// A phase of the run, in which every linter of the given kind ran.
+    Phase(LinterKind),
+    /// A linter running against a target.
+    Linter(LintSource<'l>),
+    /// Loading the contents of a file for content linters.
+    FileLoad(&'l Utf8Path),
+}
+
+/// A timed event during a lint run.
+#[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
+pub struct TimingEvent<'l> {
+    /// What was timed.
+    pub activity: TimingActivity<'l>,
+    /// When the event started.
+    pub start: Instant,
+    /// How long the event took.
+    pub duration: Duration,
+    /// The thread the event happened on: 0 for the thread the engine was run on, and 1 onwards for
+    /// threads in the engine's thread pool.
+    pub thread: usize,
+}
+
+/// The total time spent in a linter.
+#[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
+pub struct LinterTiming {
+    /// The name of the linter.
+    pub name: &'static str,
+    /// The kind of the linter.
+    pub kind: LinterKind,
+    /// The total time spent in the linter, across every target and thread.
+    pub total: Duration,
+    /// The number of calls into the linter the total is made up of. Calls to `pre_run` and `run`
+    /// are counted separately.
+    pub calls: usize,
+}
+
+fn linter_kind(kind: LintKind<'_>) -> LinterKind {
+    match kind {
+        LintKind::Project => LinterKind::Project,
+        LintKind::Package { .. } => LinterKind::Package,
+        LintKind::FilePath(_) => LinterKind::FilePath,
+        LintKind::Content(_) => LinterKind::Content,
+    }
+}
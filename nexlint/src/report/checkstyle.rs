@@ -0,0 +1,113 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! [Checkstyle](https://checkstyle.org/) XML output for lint results, as understood by Jenkins and
+//! other CI systems.
+
+use super::{message_text, xml_escape_attr, Reporter};
+use crate::prelude::*;
+use std::{collections::BTreeMap, fmt::Write as _, io::Write};
+
+/// The Checkstyle format version written out.
+const CHECKSTYLE_VERSION: &str = "4.3";
+
+/// Writes out lint messages as a Checkstyle XML document.
+///
+/// Messages are grouped into a `<file>` element per path, with project and package messages
+/// reported against the corresponding `Cargo.toml`. The `source` of each error is the linter name,
+/// prefixed with `nexlint.`. Skipped lints aren't written out.
+pub fn write_checkstyle(results: &LintResults<'_>, mut writer: impl Write) -> Result<()> {
+    let mut by_file: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for (source, message) in &results.messages {
+        by_file
+            .entry(source.kind().file_path())
+            .or_default()
+            .push((source, message));
+    }
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(xml, "<checkstyle version=\"{}\">", CHECKSTYLE_VERSION).expect("writing to a string");
+    for (path, messages) in by_file {
+        writeln!(xml, "  <file name=\"{}\">", xml_escape_attr(path.as_str()))
+            .expect("writing to a string");
+        for (source, message) in messages {
+            xml.push_str("    <error");
+            if let Some(span) = message.span() {
+                write!(
+                    xml,
+                    " line=\"{}\" column=\"{}\"",
+                    span.start().line,
+                    span.start().column
+                )
+                .expect("writing to a string");
+            }
+            writeln!(
+                xml,
+                " severity=\"{}\" message=\"{}\" source=\"nexlint.{}\"/>",
+                severity(message.level()),
+                xml_escape_attr(&message_text(message)),
+                xml_escape_attr(source.name())
+            )
+            .expect("writing to a string");
+        }
+        xml.push_str("  </file>\n");
+    }
+    xml.push_str("</checkstyle>\n");
+
+    writer
+        .write_all(xml.as_bytes())
+        .map_err(|err| SystemError::io("writing lint results", err))
+}
+
+/// A [`Reporter`] that writes out a Checkstyle XML document.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CheckstyleReporter;
+
+impl Reporter for CheckstyleReporter {
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> Result<()> {
+        write_checkstyle(results, writer)
+    }
+}
+
+fn severity(level: LintLevel) -> &'static str {
+    match level {
+        LintLevel::Error => "error",
+        LintLevel::Warning => "warning",
+        LintLevel::Note | LintLevel::Help => "info",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sample_results;
+
+    fn checkstyle(results: &LintResults<'_>) -> String {
+        let mut out = vec![];
+        write_checkstyle(results, &mut out).expect("wrote Checkstyle XML");
+        String::from_utf8(out).expect("output is valid UTF-8")
+    }
+
+    #[test]
+    fn multi_line_messages() {
+        let mut results = sample_results();
+        let (source, _) = results.messages[0];
+        let message = LintMessage::new(LintLevel::Warning, "first line\r\nsecond <line>")
+            .with_help("do \"this\"");
+        results.messages = vec![(source, message)];
+
+        // Line breaks are kept as character references, rather than normalized to spaces by XML
+        // parsers.
+        assert_eq!(
+            checkstyle(&results),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="src/lib.rs">
+    <error severity="warning" message="first line&#13;&#10;second &lt;line&gt;&#10;help: do &quot;this&quot;" source="nexlint.todo"/>
+  </file>
+</checkstyle>
+"#
+        );
+    }
+}
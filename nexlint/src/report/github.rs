@@ -0,0 +1,127 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! [GitHub Actions workflow commands](https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions)
+//! for lint results, so that messages show up as annotations on pull request diffs.
+
+use super::{message_text, Reporter};
+use crate::prelude::*;
+use std::{fmt::Write as _, io::Write};
+
+/// The environment variable GitHub Actions sets to `true` for every step.
+pub const GITHUB_ACTIONS_ENV: &str = "GITHUB_ACTIONS";
+
+/// Returns true if this process is running as part of a GitHub Actions workflow.
+pub fn is_github_actions() -> bool {
+    std::env::var(GITHUB_ACTIONS_ENV).as_deref() == Ok("true")
+}
+
+/// Writes out lint messages as GitHub Actions workflow commands, one per line.
+///
+/// Each message becomes an `::error`, `::warning` or `::notice` command titled with the linter's
+/// name. Project and package messages are reported against the corresponding `Cargo.toml`.
+/// Skipped lints aren't written out.
+pub fn write_github(results: &LintResults<'_>, mut writer: impl Write) -> Result<()> {
+    for (source, message) in &results.messages {
+        writeln!(writer, "{}", workflow_command(source, message))
+            .map_err(|err| SystemError::io("writing lint results", err))?;
+    }
+    Ok(())
+}
+
+/// A [`Reporter`] that writes out GitHub Actions workflow commands.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GithubReporter;
+
+impl Reporter for GithubReporter {
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> Result<()> {
+        write_github(results, writer)
+    }
+}
+
+fn workflow_command(source: &LintSource<'_>, message: &LintMessage) -> String {
+    let mut command = format!(
+        "::{} file={}",
+        command_name(message.level()),
+        escape_property(source.kind().file_path().as_str())
+    );
+
+    if let Some(span) = message.span() {
+        let (start, end) = (span.start(), span.end());
+        // Spans are exclusive at the end, while annotations include their last line and column.
+        // A span that ends at the start of a line doesn't include any of that line.
+        let end_line = if end.column == 1 && end.line > start.line {
+            end.line - 1
+        } else {
+            end.line
+        };
+        write!(command, ",line={},endLine={}", start.line, end_line).expect("writing to a string");
+        // Columns are only used by GitHub for annotations on a single line.
+        if end_line == start.line {
+            let end_column = end.column.saturating_sub(1).max(start.column);
+            write!(command, ",col={},endColumn={}", start.column, end_column)
+                .expect("writing to a string");
+        }
+    }
+
+    write!(
+        command,
+        ",title={}::{}",
+        escape_property(source.name()),
+        escape_data(&message_text(message))
+    )
+    .expect("writing to a string");
+    command
+}
+
+fn command_name(level: LintLevel) -> &'static str {
+    match level {
+        LintLevel::Error => "error",
+        LintLevel::Warning => "warning",
+        LintLevel::Note | LintLevel::Help => "notice",
+    }
+}
+
+/// Escapes the message of a workflow command.
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command, which additionally can't contain the
+/// separators between properties.
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sample_results;
+
+    fn github(results: &LintResults<'_>) -> String {
+        let mut out = vec![];
+        write_github(results, &mut out).expect("wrote workflow commands");
+        String::from_utf8(out).expect("output is valid UTF-8")
+    }
+
+    #[test]
+    fn workflow_commands() {
+        let mut results = sample_results();
+        assert_eq!(
+            github(&results),
+            "::error file=src/lib.rs,line=2,endLine=2,col=8,endColumn=11,title=todo::found TODO\n"
+        );
+
+        // Percent signs and newlines in the text are escaped, so the command stays on one line.
+        let (source, _) = results.messages[0];
+        let message = LintMessage::new(LintLevel::Warning, "100% of lines\nare too long")
+            .with_help("wrap them");
+        results.messages = vec![(source, message)];
+        assert_eq!(
+            github(&results),
+            "::warning file=src/lib.rs,title=todo::100%25 of lines%0Aare too long%0Ahelp: wrap them\n"
+        );
+    }
+}
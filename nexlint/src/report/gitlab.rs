@@ -0,0 +1,156 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! [GitLab Code Quality](https://docs.gitlab.com/ee/ci/testing/code_quality.html) JSON output for
+//! lint results, so that messages show up in merge request widgets.
+
+use super::{message_text, Reporter};
+use crate::prelude::*;
+use camino::Utf8Path;
+use serde::Serialize;
+use std::{borrow::Cow, collections::HashMap, io::Write};
+use twox_hash::XxHash64;
+
+/// Writes out lint messages as a GitLab Code Quality report: a JSON array of issues.
+///
+/// Project and package messages are reported against the corresponding `Cargo.toml`. Skipped
+/// lints aren't written out.
+///
+/// GitLab compares fingerprints across pipelines to work out which issues are new, so each issue's
+/// fingerprint is derived from its linter, its lint kind and the message's
+/// [fingerprint](LintMessage::fingerprint), and not from the line it's on. Identical messages for
+/// the same target are told apart by the order they're reported in.
+pub fn write_gitlab(results: &LintResults<'_>, mut writer: impl Write) -> Result<()> {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let issues: Vec<_> = results
+        .messages
+        .iter()
+        .map(|(source, message)| {
+            let key = format!(
+                "{}\0{}\0{}",
+                source.name(),
+                source.kind(),
+                message.fingerprint()
+            );
+            let occurrence = occurrences.entry(key.clone()).or_default();
+            let fingerprint = format!(
+                "{:016x}",
+                XxHash64::oneshot(0, format!("{}\0{}", key, occurrence).as_bytes())
+            );
+            *occurrence += 1;
+
+            Issue {
+                description: message_text(message),
+                check_name: source.name(),
+                fingerprint,
+                severity: severity(message.level()),
+                location: Location {
+                    path: source.kind().file_path(),
+                    lines: Lines {
+                        // GitLab requires a line, so messages without spans point at the first one.
+                        begin: message.span().map_or(1, |span| span.start().line),
+                    },
+                },
+            }
+        })
+        .collect();
+
+    serde_json::to_writer_pretty(&mut writer, &issues)
+        .map_err(|err| SystemError::ser("serializing lint results to JSON", err))?;
+    writeln!(writer).map_err(|err| SystemError::io("writing lint results", err))
+}
+
+/// A [`Reporter`] that writes out a GitLab Code Quality report.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GitlabReporter;
+
+impl Reporter for GitlabReporter {
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> Result<()> {
+        write_gitlab(results, writer)
+    }
+}
+
+fn severity(level: LintLevel) -> &'static str {
+    match level {
+        LintLevel::Error => "major",
+        LintLevel::Warning => "minor",
+        LintLevel::Note | LintLevel::Help => "info",
+    }
+}
+
+#[derive(Serialize)]
+struct Issue<'a> {
+    description: Cow<'a, str>,
+    check_name: &'a str,
+    fingerprint: String,
+    severity: &'static str,
+    location: Location<'a>,
+}
+
+#[derive(Serialize)]
+struct Location<'a> {
+    path: Cow<'a, Utf8Path>,
+    lines: Lines,
+}
+
+#[derive(Serialize)]
+struct Lines {
+    begin: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sample_results;
+    use serde_json::{json, Value};
+
+    fn gitlab(results: &LintResults<'_>) -> Value {
+        let mut out = vec![];
+        write_gitlab(results, &mut out).expect("wrote GitLab report");
+        serde_json::from_slice(&out).expect("output is valid JSON")
+    }
+
+    #[test]
+    fn gitlab_report() {
+        let value = gitlab(&sample_results());
+        assert_eq!(
+            value,
+            json!([{
+                "description": "found TODO",
+                "check_name": "todo",
+                "fingerprint": "941eee08b6d30747",
+                "severity": "major",
+                "location": { "path": "src/lib.rs", "lines": { "begin": 2 } },
+            }])
+        );
+    }
+
+    #[test]
+    fn gitlab_fingerprints_are_stable() {
+        let fingerprints = |results: &LintResults<'_>| -> Vec<Value> {
+            let value = gitlab(results);
+            let issues = value.as_array().expect("report is an array");
+            issues
+                .iter()
+                .map(|issue| issue["fingerprint"].clone())
+                .collect()
+        };
+        let first = fingerprints(&sample_results());
+        assert_eq!(fingerprints(&sample_results()), first, "same across runs");
+
+        // Moving the message to another line keeps its fingerprint.
+        let mut results = sample_results();
+        let (source, message) = results.messages.pop().expect("one message");
+        let span = LintSpan::new(LineColumn::new(10, 1), LineColumn::new(10, 5));
+        results
+            .messages
+            .push((source, message.clone().with_span(span)));
+        assert_eq!(fingerprints(&results), first, "independent of the line");
+
+        // A second identical message gets a fingerprint of its own, and the first keeps its one.
+        results.messages.push((source, message));
+        let both = fingerprints(&results);
+        assert_eq!(both[0], first[0]);
+        assert_ne!(both[1], both[0]);
+    }
+}
@@ -0,0 +1,376 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Human-readable output for lint results, in the style of rustc's diagnostics.
+//!
+//! Messages are grouped by the file they refer to. Messages from content lints are rendered with
+//! the source lines they point at, with the span underlined by carets:
+//!
+//! ```text
+//! error[trailing-whitespace]: trailing whitespace
+//!   --> src/lib.rs:12:31
+//!    |
+//! 12 |     let x = 1;
+//!    |               ^^
+//!    = help: remove trailing whitespace
+//! ```
+
+use super::Reporter;
+use crate::prelude::*;
+use camino::Utf8Path;
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    io::{self, Write},
+};
+use termcolor::{Ansi, Color, ColorSpec, NoColor, WriteColor};
+use unicode_width::UnicodeWidthStr;
+
+/// The width used for wrapping if none is specified.
+pub const DEFAULT_WIDTH: usize = 100;
+
+/// The maximum number of source lines shown for a single span.
+const MAX_SOURCE_LINES: usize = 6;
+
+/// The minimum number of columns the first line of a message is wrapped to.
+const MIN_WRAP_WIDTH: usize = 20;
+
+/// The number of columns a tab character is rendered as.
+const TAB_WIDTH: usize = 4;
+
+/// Writes out lint messages in a human-readable format, grouped by file.
+///
+/// Colors are controlled by `writer`: pass in a [`termcolor::NoColor`] to disable them. Message
+/// text is wrapped to fit within `width` columns.
+pub fn write_human(
+    results: &LintResults<'_>,
+    mut writer: impl WriteColor,
+    width: usize,
+) -> Result<()> {
+    let mut by_file: BTreeMap<Cow<'_, _>, Vec<_>> = BTreeMap::new();
+    for (source, message) in &results.messages {
+        by_file
+            .entry(source.kind().file_path())
+            .or_default()
+            .push((source, message));
+    }
+
+    let mut renderer = Renderer {
+        writer: &mut writer,
+        width,
+    };
+    for (path, mut messages) in by_file {
+        // Messages without a span sort first, then by position. The sort is stable, so messages at
+        // the same position stay in the order they were produced.
+        messages.sort_by_key(|(_, message)| message.span().map(|span| span.start()));
+        for (source, message) in messages {
+            renderer
+                .write_message(&path, source, message)
+                .map_err(|err| SystemError::io("writing lint results", err))?;
+        }
+    }
+    Ok(())
+}
+
+/// A [`Reporter`] that writes out lint messages in a human-readable format.
+#[derive(Clone, Copy, Debug)]
+pub struct HumanReporter {
+    color: bool,
+    width: usize,
+}
+
+impl HumanReporter {
+    /// Creates a new reporter that doesn't use colors, and wraps text to [`DEFAULT_WIDTH`].
+    pub fn new() -> Self {
+        Self {
+            color: false,
+            width: DEFAULT_WIDTH,
+        }
+    }
+
+    /// Sets whether to color output using ANSI escape codes.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the width to wrap text to.
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+}
+
+impl Default for HumanReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reporter for HumanReporter {
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> Result<()> {
+        if self.color {
+            write_human(results, Ansi::new(writer), self.width)
+        } else {
+            write_human(results, NoColor::new(writer), self.width)
+        }
+    }
+}
+
+struct Renderer<'w, W> {
+    writer: &'w mut W,
+    width: usize,
+}
+
+impl<W: WriteColor> Renderer<'_, W> {
+    fn write_message(
+        &mut self,
+        path: &Utf8Path,
+        source: &LintSource<'_>,
+        message: &LintMessage,
+    ) -> io::Result<()> {
+        // The gutter holds line numbers, so it's as wide as the largest one.
+        let gutter = message
+            .span()
+            .map(|span| span.end().line.to_string().len())
+            .unwrap_or(1);
+
+        // Header: "error[name]: message".
+        let level = level_name(message.level());
+        self.writer.set_color(&level_color(message.level()))?;
+        write!(self.writer, "{}", level)?;
+        write!(self.writer, "[{}]", source.name())?;
+        self.writer.set_color(ColorSpec::new().set_bold(true))?;
+        write!(self.writer, ": ")?;
+        let header_len = level.len() + source.name().len() + 4;
+        self.write_wrapped(message.message(), header_len, 2)?;
+        self.writer.reset()?;
+
+        // Location: "  --> path:line:col".
+        self.write_gutter(gutter, "-->")?;
+        match (message.span(), source.kind()) {
+            (Some(span), _) => writeln!(self.writer, " {}:{}", path, span.start())?,
+            (
+                None,
+                LintKind::Package {
+                    name,
+                    workspace_path: _,
+                },
+            ) => writeln!(self.writer, " {} (package '{}')", path, name)?,
+            (None, _) => writeln!(self.writer, " {}", path)?,
+        }
+
+        if let Some(span) = message.span() {
+            if let Some(lines) = span.source_lines() {
+                self.write_snippet(span, lines, gutter, message.level())?;
+            }
+        }
+
+        for child in message.children() {
+            self.write_gutter(gutter, "=")?;
+            self.writer.set_color(ColorSpec::new().set_bold(true))?;
+            let level = level_name(child.level());
+            write!(self.writer, " {}", level)?;
+            self.writer.reset()?;
+            write!(self.writer, ": ")?;
+            let prefix_len = gutter + level.len() + 5;
+            self.write_wrapped(child.message(), prefix_len, prefix_len)?;
+        }
+
+        writeln!(self.writer)
+    }
+
+    fn write_snippet<'a>(
+        &mut self,
+        span: &LintSpan,
+        lines: impl Iterator<Item = &'a str>,
+        gutter: usize,
+        level: LintLevel,
+    ) -> io::Result<()> {
+        self.write_gutter(gutter, "|")?;
+        writeln!(self.writer)?;
+
+        let lines: Vec<_> = lines.collect();
+        let last_idx = lines.len().saturating_sub(1);
+        for (idx, line) in lines.iter().enumerate() {
+            if idx == MAX_SOURCE_LINES {
+                self.write_gutter(gutter, "...")?;
+                writeln!(self.writer)?;
+                break;
+            }
+            let line_number = span.start().line + idx;
+
+            // Columns are 1-based, and in chars. The span ends at the end column, exclusive. Spans
+            // are constructed by linters, so treat a column of 0 as the start of the line.
+            let start_col = if idx == 0 {
+                span.start().column.max(1)
+            } else {
+                1
+            };
+            let end_col = if idx == last_idx && span.end().line == line_number {
+                span.end().column
+            } else {
+                line.chars().count() + 1
+            };
+
+            self.writer.set_color(&gutter_color())?;
+            write!(self.writer, "{:>gutter$} |", line_number, gutter = gutter)?;
+            self.writer.reset()?;
+            let rendered = expand_tabs(line);
+            if rendered.is_empty() {
+                writeln!(self.writer)?;
+            } else {
+                writeln!(self.writer, " {}", rendered)?;
+            }
+
+            let prefix: String = line.chars().take(start_col - 1).collect();
+            let highlighted: String = line
+                .chars()
+                .skip(start_col - 1)
+                .take(end_col.saturating_sub(start_col))
+                .collect();
+            let offset = expand_tabs(&prefix).width();
+            // Zero-width spans, such as a missing newline at EOF, still get a single caret.
+            let carets = expand_tabs(&highlighted).width().max(1);
+
+            self.write_gutter(gutter, "|")?;
+            write!(self.writer, " {:offset$}", "", offset = offset)?;
+            self.writer.set_color(&level_color(level))?;
+            writeln!(self.writer, "{}", "^".repeat(carets))?;
+            self.writer.reset()?;
+        }
+        Ok(())
+    }
+
+    /// Writes out the gutter followed by a marker, e.g. " --> " or "  | ".
+    fn write_gutter(&mut self, gutter: usize, marker: &str) -> io::Result<()> {
+        self.writer.set_color(&gutter_color())?;
+        // Single-column markers line up with the line separator, longer ones start one column to
+        // the left of it.
+        let padding = if marker.len() == 1 {
+            gutter + 1
+        } else {
+            gutter
+        };
+        write!(self.writer, "{:padding$}{}", "", marker, padding = padding)?;
+        self.writer.reset()
+    }
+
+    /// Writes out text wrapped to the configured width. The first line continues a line that's
+    /// already `offset` columns wide, and later lines are indented by `indent` columns.
+    fn write_wrapped(&mut self, text: &str, offset: usize, indent: usize) -> io::Result<()> {
+        let initial_indent = " ".repeat(offset);
+        let subsequent_indent = " ".repeat(indent);
+        let wrapper = textwrap::Wrapper::new(self.width.max(offset + MIN_WRAP_WIDTH))
+            .initial_indent(&initial_indent)
+            .subsequent_indent(&subsequent_indent);
+        let mut lines = 0;
+        for (idx, line) in wrapper.wrap_iter(text).enumerate() {
+            let line = if idx == 0 { &line[offset..] } else { &*line };
+            writeln!(self.writer, "{}", line)?;
+            lines += 1;
+        }
+        if lines == 0 {
+            // Empty text still ends the line it continues.
+            writeln!(self.writer)?;
+        }
+        Ok(())
+    }
+}
+
+fn level_name(level: LintLevel) -> &'static str {
+    match level {
+        LintLevel::Error => "error",
+        LintLevel::Warning => "warning",
+        LintLevel::Note => "note",
+        LintLevel::Help => "help",
+    }
+}
+
+fn level_color(level: LintLevel) -> ColorSpec {
+    let color = match level {
+        LintLevel::Error => Color::Red,
+        LintLevel::Warning => Color::Yellow,
+        LintLevel::Note => Color::Green,
+        LintLevel::Help => Color::Cyan,
+    };
+    let mut spec = ColorSpec::new();
+    spec.set_fg(Some(color)).set_bold(true).set_intense(true);
+    spec
+}
+
+fn gutter_color() -> ColorSpec {
+    let mut spec = ColorSpec::new();
+    spec.set_fg(Some(Color::Blue))
+        .set_bold(true)
+        .set_intense(true);
+    spec
+}
+
+fn expand_tabs(text: &str) -> Cow<'_, str> {
+    if text.contains('\t') {
+        Cow::Owned(text.replace('\t', &" ".repeat(TAB_WIDTH)))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(start: LineColumn, end: LineColumn, lines: &[&str]) -> String {
+        let mut out = NoColor::new(vec![]);
+        let mut renderer = Renderer {
+            writer: &mut out,
+            width: DEFAULT_WIDTH,
+        };
+        let span = LintSpan::new(start, end);
+        renderer
+            .write_snippet(&span, lines.iter().copied(), 1, LintLevel::Error)
+            .expect("wrote snippet");
+        String::from_utf8(out.into_inner()).expect("snippet is valid UTF-8")
+    }
+
+    #[test]
+    fn snippet_carets() {
+        let at = LineColumn::new;
+        assert_eq!(
+            snippet(at(1, 2), at(1, 4), &["abcdef"]),
+            "  |\n1 | abcdef\n  |  ^^\n"
+        );
+        // Zero-width spans still get a caret.
+        assert_eq!(
+            snippet(at(1, 7), at(1, 7), &["abcdef"]),
+            "  |\n1 | abcdef\n  |       ^\n"
+        );
+        // Column 0 is treated as column 1, and an end before the start as an empty span.
+        assert_eq!(
+            snippet(at(1, 0), at(1, 3), &["abcdef"]),
+            "  |\n1 | abcdef\n  | ^^\n"
+        );
+        assert_eq!(
+            snippet(at(1, 4), at(1, 2), &["abcdef"]),
+            "  |\n1 | abcdef\n  |    ^\n"
+        );
+    }
+
+    #[test]
+    fn empty_message() {
+        let mut out = NoColor::new(vec![]);
+        let mut renderer = Renderer {
+            writer: &mut out,
+            width: DEFAULT_WIDTH,
+        };
+        let path = Utf8Path::new("a.rs");
+        let source = LintSource::new("lint", LintKind::Content(path));
+        let message = LintMessage::new(LintLevel::Error, "").with_help("");
+        renderer
+            .write_message(path, &source, &message)
+            .expect("wrote message");
+        assert_eq!(
+            String::from_utf8(out.into_inner()).expect("message is valid UTF-8"),
+            "error[lint]: \n --> a.rs\n  = help: \n\n"
+        );
+    }
+}
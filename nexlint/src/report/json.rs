@@ -0,0 +1,130 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! JSON and JSON-lines output for lint results.
+//!
+//! Both formats use the same record shape for each message and skipped lint. Keys are kebab-case,
+//! and the `linter` and `kind` keys identify where a record came from.
+
+use super::Reporter;
+use crate::{
+    lint::runner::{MessageRecord, SkipRecord},
+    prelude::*,
+};
+use serde::Serialize;
+use std::io::Write;
+
+/// Writes out lint results as a single JSON document, followed by a newline.
+///
+/// The document is an object with `messages` and `skipped` arrays.
+pub fn write_json(results: &LintResults<'_>, mut writer: impl Write) -> Result<()> {
+    serde_json::to_writer(&mut writer, results)
+        .map_err(|err| SystemError::ser("serializing lint results to JSON", err))?;
+    writeln!(writer).map_err(|err| SystemError::io("writing lint results", err))
+}
+
+/// Writes out lint results as JSON lines: one JSON object per message or skipped lint.
+///
+/// Each object has a `type` key that is either `"message"` or `"skip"`. Messages are written out
+/// before skipped lints.
+pub fn write_json_lines(results: &LintResults<'_>, mut writer: impl Write) -> Result<()> {
+    let messages = results
+        .messages
+        .iter()
+        .map(|(source, message)| JsonLine::Message(MessageRecord { source, message }));
+    let skipped = results
+        .skipped
+        .iter()
+        .map(|(source, reason)| JsonLine::Skip(SkipRecord { source, reason }));
+
+    for line in messages.chain(skipped) {
+        serde_json::to_writer(&mut writer, &line)
+            .map_err(|err| SystemError::ser("serializing lint results to JSON", err))?;
+        writeln!(writer).map_err(|err| SystemError::io("writing lint results", err))?;
+    }
+    Ok(())
+}
+
+/// A [`Reporter`] that writes out a single JSON document.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonReporter;
+
+impl Reporter for JsonReporter {
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> Result<()> {
+        write_json(results, writer)
+    }
+}
+
+/// A [`Reporter`] that writes out JSON lines.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonLinesReporter;
+
+impl Reporter for JsonLinesReporter {
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> Result<()> {
+        write_json_lines(results, writer)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum JsonLine<'a, 'l> {
+    Message(MessageRecord<'a, 'l>),
+    Skip(SkipRecord<'a, 'l>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sample_results;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn json_schema() {
+        let mut out = vec![];
+        write_json(&sample_results(), &mut out).expect("wrote JSON");
+        assert_eq!(out.last(), Some(&b'\n'), "output ends with a newline");
+        let value: Value = serde_json::from_slice(&out).expect("output is valid JSON");
+
+        let message = json!({
+            "linter": "todo",
+            "kind": { "type": "content", "path": "src/lib.rs" },
+            "level": "error",
+            "message": "found TODO",
+            "span": {
+                "start": { "line": 2, "column": 8 },
+                "end": { "line": 2, "column": 12 },
+            },
+            "fix": {
+                "description": "remove TODO",
+                "edits": [{
+                    "path": "src/lib.rs",
+                    "range": { "start": 19, "end": 23 },
+                    "replacement": "",
+                }],
+            },
+        });
+        let skip = json!({
+            "linter": "todo",
+            "kind": { "type": "content", "path": "image.png" },
+            "reason": "binary-file",
+        });
+        assert_eq!(
+            value,
+            json!({ "messages": [message.clone()], "skipped": [skip.clone()] })
+        );
+
+        // JSON lines use the same records, tagged with their type.
+        let mut out = vec![];
+        write_json_lines(&sample_results(), &mut out).expect("wrote JSON lines");
+        let lines: Vec<Value> = String::from_utf8(out)
+            .expect("output is valid UTF-8")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("line is valid JSON"))
+            .collect();
+        let tagged = |mut record: Value, ty: &str| {
+            record["type"] = ty.into();
+            record
+        };
+        assert_eq!(lines, [tagged(message, "message"), tagged(skip, "skip")]);
+    }
+}
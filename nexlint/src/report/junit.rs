@@ -0,0 +1,330 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! JUnit XML output for lint results, for CI systems that ingest test reports.
+//!
+//! Each linter becomes a `<testsuite>`, and each target it ran against (the project, a package, or
+//! a file) becomes a `<testcase>` within it. A test case fails if the linter produced any messages
+//! at or above the failure level for its target, and is marked as skipped if the linter was
+//! skipped for that target. Messages below the failure level are written to `<system-out>`.
+
+use super::{xml_escape as escape, xml_escape_attr as escape_attr, Reporter};
+use crate::prelude::*;
+use std::{collections::BTreeMap, fmt::Write as _, io::Write};
+
+/// The name of the top-level `<testsuites>` element.
+const TESTSUITES_NAME: &str = "nexlint";
+
+/// Writes out lint results as a JUnit XML document.
+///
+/// Messages at or above `fail_level` fail their test case. If `fail_level` is `None`, no test case
+/// fails.
+pub fn write_junit(
+    results: &LintResults<'_>,
+    mut writer: impl Write,
+    fail_level: Option<LintLevel>,
+) -> Result<()> {
+    let suites = TestSuites::new(results, fail_level);
+    writer
+        .write_all(suites.to_xml().as_bytes())
+        .map_err(|err| SystemError::io("writing lint results", err))
+}
+
+/// A [`Reporter`] that writes out a JUnit XML document.
+#[derive(Clone, Copy, Debug)]
+pub struct JunitReporter {
+    fail_level: Option<LintLevel>,
+}
+
+impl JunitReporter {
+    /// Creates a new reporter that fails test cases with errors.
+    pub fn new() -> Self {
+        Self {
+            fail_level: Some(LintLevel::Error),
+        }
+    }
+
+    /// Sets the lowest level of message that fails a test case, or `None` for no failures.
+    pub fn with_fail_level(mut self, fail_level: Option<LintLevel>) -> Self {
+        self.fail_level = fail_level;
+        self
+    }
+}
+
+impl Default for JunitReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reporter for JunitReporter {
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> Result<()> {
+        write_junit(results, writer, self.fail_level)
+    }
+}
+
+#[derive(Default)]
+struct TestSuites<'a> {
+    suites: BTreeMap<&'a str, BTreeMap<String, TestCase<'a>>>,
+}
+
+#[derive(Default)]
+struct TestCase<'a> {
+    /// Messages at or above the failure level.
+    failures: Vec<&'a LintMessage>,
+    /// Messages below the failure level.
+    output: Vec<&'a LintMessage>,
+    skipped: Vec<&'a SkipReason<'a>>,
+}
+
+impl<'a> TestSuites<'a> {
+    fn new(results: &'a LintResults<'a>, fail_level: Option<LintLevel>) -> Self {
+        let mut suites = Self::default();
+        for source in &results.executed {
+            suites.case(source);
+        }
+        for (source, message) in &results.messages {
+            let case = suites.case(source);
+            if fail_level.is_some_and(|fail_level| message.level().is_at_least(fail_level)) {
+                case.failures.push(message);
+            } else {
+                case.output.push(message);
+            }
+        }
+        for (source, reason) in &results.skipped {
+            suites.case(source).skipped.push(reason);
+        }
+        suites
+    }
+
+    fn case(&mut self, source: &LintSource<'a>) -> &mut TestCase<'a> {
+        self.suites
+            .entry(source.name())
+            .or_default()
+            .entry(target_name(source.kind()))
+            .or_default()
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = String::new();
+        let (tests, failures, skipped) = self
+            .suites
+            .values()
+            .map(counts)
+            .fold((0, 0, 0), |acc, counts| {
+                (acc.0 + counts.0, acc.1 + counts.1, acc.2 + counts.2)
+            });
+
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        writeln!(
+            xml,
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\">",
+            TESTSUITES_NAME, tests, failures, skipped
+        )
+        .expect("writing to a string");
+
+        for (name, cases) in &self.suites {
+            let (tests, failures, skipped) = counts(cases);
+            writeln!(
+                xml,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\">",
+                escape_attr(name),
+                tests,
+                failures,
+                skipped
+            )
+            .expect("writing to a string");
+            for (target, case) in cases {
+                case.write_xml(name, target, &mut xml);
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+}
+
+impl TestCase<'_> {
+    fn is_failure(&self) -> bool {
+        !self.failures.is_empty()
+    }
+
+    /// A test case is only skipped if the linter didn't run for it at all. Messages that were
+    /// suppressed or baselined don't stop a test case from passing.
+    fn is_skipped(&self) -> bool {
+        self.failures.is_empty()
+            && self.output.is_empty()
+            && !self.skipped.is_empty()
+            && self.skipped.iter().all(|reason| {
+                !matches!(reason, SkipReason::Suppressed(_) | SkipReason::Baselined(_))
+            })
+    }
+
+    fn write_xml(&self, suite: &str, target: &str, xml: &mut String) {
+        write!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"{}\"",
+            escape_attr(target),
+            escape_attr(suite)
+        )
+        .expect("writing to a string");
+
+        if !self.is_failure() && !self.is_skipped() && self.output.is_empty() {
+            xml.push_str("/>\n");
+            return;
+        }
+
+        xml.push_str(">\n");
+        if self.is_failure() {
+            let level = self
+                .failures
+                .iter()
+                .map(|message| message.level())
+                .min_by_key(|level| level_rank(*level))
+                .expect("failures have at least one message");
+            let summary = match self.failures.as_slice() {
+                [message] => message.message().to_owned(),
+                messages => format!("{} lint messages", messages.len()),
+            };
+            writeln!(
+                xml,
+                "      <failure type=\"{}\" message=\"{}\">{}</failure>",
+                level.to_string().to_lowercase(),
+                escape_attr(&summary),
+                escape(&messages_text(&self.failures)),
+            )
+            .expect("writing to a string");
+        } else if self.is_skipped() {
+            let reasons: Vec<_> = self
+                .skipped
+                .iter()
+                .map(|reason| reason.to_string())
+                .collect();
+            writeln!(
+                xml,
+                "      <skipped message=\"{}\"/>",
+                escape_attr(&reasons.join("; "))
+            )
+            .expect("writing to a string");
+        }
+        if !self.output.is_empty() {
+            writeln!(
+                xml,
+                "      <system-out>{}</system-out>",
+                escape(&messages_text(&self.output))
+            )
+            .expect("writing to a string");
+        }
+        xml.push_str("    </testcase>\n");
+    }
+}
+
+/// Returns the body of a failure or output element: every message, with its location and children.
+fn messages_text(messages: &[&LintMessage]) -> String {
+    let mut text = String::new();
+    for message in messages {
+        write!(
+            text,
+            "{}: {}",
+            message.level().to_string().to_lowercase(),
+            message.message()
+        )
+        .expect("writing to a string");
+        if let Some(span) = message.span() {
+            write!(text, " (at {})", span).expect("writing to a string");
+        }
+        text.push('\n');
+        for child in message.children() {
+            writeln!(
+                text,
+                "  = {}: {}",
+                child.level().to_string().to_lowercase(),
+                child.message()
+            )
+            .expect("writing to a string");
+        }
+    }
+    text
+}
+
+/// Returns the name of the test case for a target.
+///
+/// Files are named by their path alone: content linters are skipped before the file is loaded, so
+/// their skipped lints have a file path kind while the lints that ran have a content kind.
+fn target_name(kind: LintKind<'_>) -> String {
+    match kind {
+        LintKind::Project => "project".to_owned(),
+        LintKind::Package { name, .. } => format!("package {}", name),
+        LintKind::FilePath(path) | LintKind::Content(path) => path.to_string(),
+    }
+}
+
+/// Returns the number of (tests, failures, skipped tests) in a test suite.
+fn counts(cases: &BTreeMap<String, TestCase<'_>>) -> (usize, usize, usize) {
+    let failures = cases.values().filter(|case| case.is_failure()).count();
+    let skipped = cases.values().filter(|case| case.is_skipped()).count();
+    (cases.len(), failures, skipped)
+}
+
+/// Orders levels from most to least severe.
+fn level_rank(level: LintLevel) -> u8 {
+    match level {
+        LintLevel::Error => 0,
+        LintLevel::Warning => 1,
+        LintLevel::Note => 2,
+        LintLevel::Help => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sample_results;
+
+    fn junit(results: &LintResults<'_>, fail_level: Option<LintLevel>) -> String {
+        let mut out = vec![];
+        write_junit(results, &mut out, fail_level).expect("wrote JUnit XML");
+        String::from_utf8(out).expect("output is valid UTF-8")
+    }
+
+    #[test]
+    fn failures_at_fail_level() {
+        let mut results = sample_results();
+        let source = results.executed[1];
+        let warning = LintMessage::new(LintLevel::Warning, "trailing whitespace");
+        results.messages.push((source, warning));
+        assert_eq!(
+            junit(&results, Some(LintLevel::Error)),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="nexlint" tests="3" failures="1" errors="0" skipped="1">
+  <testsuite name="todo" tests="2" failures="1" errors="0" skipped="1">
+    <testcase name="image.png" classname="todo">
+      <skipped message="binary file"/>
+    </testcase>
+    <testcase name="src/lib.rs" classname="todo">
+      <failure type="error" message="found TODO">error: found TODO (at 2:8)
+</failure>
+    </testcase>
+  </testsuite>
+  <testsuite name="whitespace" tests="1" failures="0" errors="0" skipped="0">
+    <testcase name="src/lib.rs" classname="whitespace">
+      <system-out>warning: trailing whitespace
+</system-out>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+
+        // Lowering the failure level fails the test case with the warning.
+        let xml = junit(&results, Some(LintLevel::Warning));
+        assert!(xml.contains(r#"<testsuites name="nexlint" tests="3" failures="2""#));
+        assert!(!xml.contains("<system-out>"), "{}", xml);
+
+        // With no failure level, every message is output.
+        let xml = junit(&results, None);
+        assert!(xml.contains(r#"<testsuites name="nexlint" tests="3" failures="0""#));
+        assert_eq!(xml.matches("<system-out>").count(), 2, "{}", xml);
+    }
+}
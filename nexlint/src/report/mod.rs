@@ -0,0 +1,85 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Reporters that write out lint results, for people and for other tools.
+//!
+//! Every format is available both as a free function and as an implementation of [`Reporter`], so
+//! that callers can pick a format at runtime or plug in their own.
+
+pub mod checkstyle;
+pub mod github;
+pub mod gitlab;
+pub mod human;
+pub mod json;
+pub mod junit;
+pub mod sarif;
+pub mod timings;
+
+use crate::prelude::*;
+use std::{borrow::Cow, io::Write};
+
+/// Writes out lint results in some format.
+pub trait Reporter {
+    /// Writes out `results` to `writer`.
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> Result<()>;
+}
+
+impl<R: Reporter + ?Sized> Reporter for &R {
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> Result<()> {
+        (**self).report(results, writer)
+    }
+}
+
+impl<R: Reporter + ?Sized> Reporter for Box<R> {
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> Result<()> {
+        (**self).report(results, writer)
+    }
+}
+
+/// Returns the text of a message, with any notes and help messages on lines after it.
+pub(crate) fn message_text(message: &LintMessage) -> Cow<'_, str> {
+    if message.children().is_empty() {
+        return Cow::Borrowed(message.message());
+    }
+    let mut text = message.message().to_owned();
+    for child in message.children() {
+        text.push_str(&format!(
+            "\n{}: {}",
+            child.level().to_string().to_lowercase(),
+            child.message()
+        ));
+    }
+    Cow::Owned(text)
+}
+
+/// Escapes text for use in XML element content.
+pub(crate) fn xml_escape(text: &str) -> String {
+    escape_xml(text, false)
+}
+
+/// Escapes text for use in XML attribute values.
+///
+/// Parsers normalize line breaks and tabs in attribute values to spaces, so they're written as
+/// character references instead.
+pub(crate) fn xml_escape_attr(text: &str) -> String {
+    escape_xml(text, true)
+}
+
+fn escape_xml(text: &str, attr: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' | '\r' | '\t' if attr => escaped.push_str(&format!("&#{};", c as u32)),
+            '\n' | '\t' => escaped.push(c),
+            // Other control characters aren't allowed in XML 1.0 at all.
+            c if c.is_control() => escaped.push('\u{fffd}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,352 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html) output for lint
+//! results, suitable for uploading to code scanning services.
+
+use super::{message_text, Reporter};
+use crate::prelude::*;
+use camino::Utf8Path;
+use serde::Serialize;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+/// Artifact paths are relative to this base ID, which consumers resolve to the project root.
+const SRCROOT: &str = "%SRCROOT%";
+
+/// Writes out lint results as a SARIF 2.1.0 log with a single run.
+///
+/// Every linter that ran, produced a message or was skipped becomes a rule, sorted by name. Project
+/// and package messages are reported against the corresponding `Cargo.toml`.
+pub fn write_sarif(results: &LintResults<'_>, mut writer: impl Write) -> Result<()> {
+    let rule_names = results
+        .executed
+        .iter()
+        .map(|source| source.name())
+        .chain(results.messages.iter().map(|(source, _)| source.name()))
+        .chain(results.skipped.iter().map(|(source, _)| source.name()));
+    let rule_indexes: BTreeMap<&str, usize> = rule_names
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .enumerate()
+        .map(|(idx, name)| (name, idx))
+        .collect();
+
+    let rules = rule_indexes
+        .keys()
+        .map(|name| SarifRule { id: name })
+        .collect();
+    let sarif_results = results
+        .messages
+        .iter()
+        .map(|(source, message)| SarifResult::new(source, message, rule_indexes[source.name()]))
+        .collect();
+
+    let log = SarifLog {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "nexlint",
+                    version: env!("CARGO_PKG_VERSION"),
+                    rules,
+                },
+            },
+            original_uri_base_ids: BTreeMap::from([(
+                SRCROOT,
+                SarifArtifactLocation {
+                    uri: "./".to_owned(),
+                    uri_base_id: None,
+                },
+            )]),
+            column_kind: "unicodeCodePoints",
+            results: sarif_results,
+        }],
+    };
+
+    serde_json::to_writer_pretty(&mut writer, &log)
+        .map_err(|err| SystemError::ser("serializing lint results to SARIF", err))?;
+    writeln!(writer).map_err(|err| SystemError::io("writing lint results", err))
+}
+
+/// A [`Reporter`] that writes out a SARIF 2.1.0 log.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SarifReporter;
+
+impl Reporter for SarifReporter {
+    fn report(&self, results: &LintResults<'_>, writer: &mut dyn Write) -> Result<()> {
+        write_sarif(results, writer)
+    }
+}
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRun<'a> {
+    tool: SarifTool<'a>,
+    original_uri_base_ids: BTreeMap<&'static str, SarifArtifactLocation>,
+    column_kind: &'static str,
+    results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifTool<'a> {
+    driver: SarifDriver<'a>,
+}
+
+#[derive(Serialize)]
+struct SarifDriver<'a> {
+    name: &'static str,
+    version: &'static str,
+    rules: Vec<SarifRule<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifRule<'a> {
+    id: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: &'static str,
+    rule_index: usize,
+    level: &'static str,
+    message: SarifMessage<'a>,
+    locations: Vec<SarifLocation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<SarifFix<'a>>,
+}
+
+impl<'a> SarifResult<'a> {
+    fn new(source: &LintSource<'_>, message: &'a LintMessage, rule_index: usize) -> Self {
+        let region = message.span().map(|span| SarifRegion {
+            start_line: Some(span.start().line),
+            start_column: Some(span.start().column),
+            end_line: Some(span.end().line),
+            end_column: Some(span.end().column),
+            ..SarifRegion::default()
+        });
+        let location = SarifLocation {
+            physical_location: SarifPhysicalLocation {
+                artifact_location: SarifArtifactLocation::new(&source.kind().file_path()),
+                region,
+            },
+        };
+        let fixes = message.fix().map(SarifFix::new).into_iter().collect();
+
+        Self {
+            rule_id: source.name(),
+            rule_index,
+            level: sarif_level(message.level()),
+            message: SarifMessage {
+                text: message_text(message),
+            },
+            locations: vec![location],
+            fixes,
+        }
+    }
+}
+
+fn sarif_level(level: LintLevel) -> &'static str {
+    match level {
+        LintLevel::Error => "error",
+        LintLevel::Warning => "warning",
+        LintLevel::Note | LintLevel::Help => "note",
+    }
+}
+
+#[derive(Serialize)]
+struct SarifMessage<'a> {
+    text: Cow<'a, str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<SarifRegion>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+impl SarifArtifactLocation {
+    fn new(path: &Utf8Path) -> Self {
+        // SARIF URIs always use forward slashes.
+        let uri = path
+            .components()
+            .map(|component| component.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        Self {
+            uri,
+            uri_base_id: Some(SRCROOT),
+        }
+    }
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    byte_offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    byte_length: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifFix<'a> {
+    description: SarifMessage<'a>,
+    artifact_changes: Vec<SarifArtifactChange<'a>>,
+}
+
+impl<'a> SarifFix<'a> {
+    fn new(fix: &'a LintFix) -> Self {
+        // Group edits by file, preserving the order in which files first appear.
+        let mut artifact_changes: Vec<SarifArtifactChange<'a>> = vec![];
+        for edit in fix.edits() {
+            let replacement = SarifReplacement {
+                deleted_region: SarifRegion {
+                    byte_offset: Some(edit.range().start),
+                    byte_length: Some(edit.range().len()),
+                    ..SarifRegion::default()
+                },
+                inserted_content: SarifInsertedContent {
+                    text: edit.replacement(),
+                },
+            };
+            match artifact_changes
+                .iter_mut()
+                .find(|change| change.path == edit.path())
+            {
+                Some(change) => change.replacements.push(replacement),
+                None => artifact_changes.push(SarifArtifactChange {
+                    path: edit.path(),
+                    artifact_location: SarifArtifactLocation::new(edit.path()),
+                    replacements: vec![replacement],
+                }),
+            }
+        }
+
+        Self {
+            description: SarifMessage {
+                text: Cow::Borrowed(fix.description()),
+            },
+            artifact_changes,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactChange<'a> {
+    #[serde(skip)]
+    path: &'a Utf8Path,
+    artifact_location: SarifArtifactLocation,
+    replacements: Vec<SarifReplacement<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifReplacement<'a> {
+    deleted_region: SarifRegion,
+    inserted_content: SarifInsertedContent<'a>,
+}
+
+#[derive(Serialize)]
+struct SarifInsertedContent<'a> {
+    text: &'a str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sample_results;
+    use serde_json::{json, Value};
+
+    fn sarif(results: &LintResults<'_>) -> Value {
+        let mut out = vec![];
+        write_sarif(results, &mut out).expect("wrote SARIF");
+        serde_json::from_slice(&out).expect("output is valid JSON")
+    }
+
+    #[test]
+    fn sarif_log() {
+        let value = sarif(&sample_results());
+
+        // The fields SARIF 2.1.0 requires for a log with results and fixes.
+        assert_eq!(value["version"], "2.1.0");
+        let run = &value["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "nexlint");
+        let result = &run["results"][0];
+        assert_eq!(result["message"]["text"], "found TODO");
+        let fix = &result["fixes"][0];
+        assert_eq!(
+            fix["artifactChanges"][0]["artifactLocation"]["uri"],
+            "src/lib.rs"
+        );
+        assert_eq!(
+            fix["artifactChanges"][0]["replacements"][0]["deletedRegion"],
+            json!({ "byteOffset": 19, "byteLength": 4 })
+        );
+
+        // Every linter that ran is a rule, even if it didn't produce any messages, and results
+        // point at their rule by index.
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            json!([{ "id": "todo" }, { "id": "whitespace" }])
+        );
+        assert_eq!(result["ruleId"], "todo");
+        assert_eq!(result["ruleIndex"], 0);
+        assert_eq!(result["level"], "error");
+        assert_eq!(
+            result["locations"],
+            json!([{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": "src/lib.rs", "uriBaseId": "%SRCROOT%" },
+                    "region": { "startLine": 2, "startColumn": 8, "endLine": 2, "endColumn": 12 },
+                },
+            }])
+        );
+
+        // Rules are in the same order regardless of the order linters ran in.
+        let mut results = sample_results();
+        results.executed.reverse();
+        assert_eq!(sarif(&results), value);
+    }
+}
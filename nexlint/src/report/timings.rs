@@ -0,0 +1,134 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Output for the timings recorded during a lint run: a table for people, and a trace that can be
+//! loaded into `chrome://tracing` or [Perfetto](https://ui.perfetto.dev/).
+
+use crate::prelude::*;
+use serde::Serialize;
+use std::{borrow::Cow, fmt::Write as _, io::Write, time::Duration};
+
+/// Writes out a table of the time spent in each linter, from slowest to fastest, followed by the
+/// wall time of each phase and the time spent loading files.
+pub fn write_timings(results: &LintResults<'_>, mut writer: impl Write) -> Result<()> {
+    let timings = &results.timings;
+    let by_linter = timings.by_linter();
+    let name_width = by_linter
+        .iter()
+        .map(|timing| timing.name.len())
+        .chain(["LINTER".len()])
+        .max()
+        .unwrap_or_default();
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{:name_width$}  {:9}  {:>12}  {:>7}",
+        "LINTER", "KIND", "TOTAL", "CALLS"
+    )
+    .expect("writing to a string");
+    for timing in &by_linter {
+        writeln!(
+            out,
+            "{:name_width$}  {:9}  {:>12}  {:>7}",
+            timing.name,
+            timing.kind,
+            millis(timing.total),
+            timing.calls,
+        )
+        .expect("writing to a string");
+    }
+
+    out.push('\n');
+    writeln!(out, "{:9}  {:>12}", "PHASE", "TIME").expect("writing to a string");
+    for (kind, duration) in timings.by_phase() {
+        writeln!(out, "{:9}  {:>12}", kind, millis(duration)).expect("writing to a string");
+    }
+    writeln!(out, "\nfile loading: {}", millis(timings.file_load())).expect("writing to a string");
+
+    writer
+        .write_all(out.as_bytes())
+        .map_err(|err| SystemError::io("writing lint timings", err))
+}
+
+/// Writes out the timings as a JSON trace in Chrome's Trace Event Format, which can be loaded into
+/// `chrome://tracing` or Perfetto.
+///
+/// Every event is a complete event on the thread it happened on, with timestamps in microseconds
+/// since the earliest event.
+pub fn write_chrome_trace(results: &LintResults<'_>, mut writer: impl Write) -> Result<()> {
+    let events = results.timings.events();
+    let epoch = events.iter().map(|event| event.start).min();
+    let trace_events: Vec<_> = events
+        .iter()
+        .map(|event| {
+            let (name, category, target) = match event.activity {
+                TimingActivity::Phase(kind) => {
+                    (Cow::Owned(format!("{} linters", kind)), "phase", None)
+                }
+                TimingActivity::Linter(source) => (
+                    Cow::Borrowed(source.name()),
+                    "linter",
+                    Some(source.kind().to_string()),
+                ),
+                TimingActivity::FileLoad(path) => (
+                    Cow::Borrowed("load file"),
+                    "file-load",
+                    Some(path.to_string()),
+                ),
+            };
+            let since_epoch = epoch.map_or(Duration::ZERO, |epoch| event.start - epoch);
+            TraceEvent {
+                name,
+                cat: category,
+                ph: "X",
+                ts: micros(since_epoch),
+                dur: micros(event.duration),
+                pid: 1,
+                tid: event.thread,
+                args: TraceArgs { target },
+            }
+        })
+        .collect();
+
+    let trace = Trace {
+        trace_events,
+        display_time_unit: "ms",
+    };
+    serde_json::to_writer(&mut writer, &trace)
+        .map_err(|err| SystemError::ser("serializing lint timings to JSON", err))?;
+    writeln!(writer).map_err(|err| SystemError::io("writing lint timings", err))
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000_000.0
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace<'a> {
+    trace_events: Vec<TraceEvent<'a>>,
+    display_time_unit: &'static str,
+}
+
+#[derive(Serialize)]
+struct TraceEvent<'a> {
+    name: Cow<'a, str>,
+    cat: &'static str,
+    ph: &'static str,
+    ts: f64,
+    dur: f64,
+    pid: u32,
+    tid: usize,
+    args: TraceArgs,
+}
+
+#[derive(Serialize)]
+struct TraceArgs {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+}
@@ -0,0 +1,190 @@
+// Copyright (c) The nextest Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Helpers for tests that run the lint engine against a scratch Git repository.
+
+use crate::{prelude::*, NexLintContext};
+use camino::{Utf8Path, Utf8PathBuf};
+use std::{
+    fs,
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tempfile::TempDir;
+
+/// A Git repository in a temporary directory, which is deleted when this is dropped.
+pub(crate) struct TestRepo {
+    _dir: TempDir,
+    root: Utf8PathBuf,
+}
+
+impl TestRepo {
+    /// Creates a repository with the given files added to the index, but not committed.
+    pub(crate) fn new(files: &[(&str, &str)]) -> Self {
+        let dir = tempfile::tempdir().expect("created temp dir");
+        // Git reports the canonical path of the repository, so use that as the root.
+        let root = dir.path().canonicalize().expect("canonicalized temp dir");
+        let root = Utf8PathBuf::try_from(root).expect("temp dir is valid UTF-8");
+        let repo = Self { _dir: dir, root };
+
+        repo.git(&["init", "-q"]);
+        for (path, contents) in files {
+            repo.write(path, contents);
+        }
+        repo.git(&["add", "."]);
+        repo
+    }
+
+    pub(crate) fn root(&self) -> &Utf8Path {
+        &self.root
+    }
+
+    /// Writes out a file, relative to the root of the repository.
+    pub(crate) fn write(&self, path: &str, contents: &str) {
+        let full_path = self.root.join(path);
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent).expect("created parent directory");
+        }
+        fs::write(&full_path, contents).expect("wrote file");
+    }
+
+    /// Reads a file, relative to the root of the repository.
+    pub(crate) fn read(&self, path: &str) -> String {
+        fs::read_to_string(self.root.join(path)).expect("read file")
+    }
+
+    /// Runs a Git command in the repository, panicking if it fails.
+    pub(crate) fn git(&self, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(&self.root)
+            .args([
+                "-c",
+                "user.name=nexlint",
+                "-c",
+                "user.email=nexlint@example.com",
+            ])
+            .args(args)
+            .status()
+            .expect("ran git");
+        assert!(status.success(), "git {:?} failed with {}", args, status);
+    }
+
+    /// Returns a context for the repository, as if run from its root.
+    pub(crate) fn context(&self) -> NexLintContext {
+        NexLintContext::new(self.root.clone()).expect("created context")
+    }
+}
+
+/// Returns results with one message that has a span and a fix, and one skipped lint, for testing
+/// reporters.
+///
+/// `todo` ran against `src/lib.rs` and `image.png`, and `whitespace` ran against `src/lib.rs`
+/// without finding anything.
+pub(crate) fn sample_results() -> LintResults<'static> {
+    let lib_rs = LintKind::Content(Utf8Path::new("src/lib.rs"));
+    let image = LintKind::Content(Utf8Path::new("image.png"));
+    let message = LintMessage::new(LintLevel::Error, "found TODO")
+        .with_span(LintSpan::new(LineColumn::new(2, 8), LineColumn::new(2, 12)))
+        .with_fix(LintFix::new(
+            "remove TODO",
+            vec![TextEdit::delete("src/lib.rs", 19..23)],
+        ));
+
+    let mut results = LintResults::new();
+    results.executed = vec![
+        LintSource::new("todo", lib_rs),
+        LintSource::new("whitespace", lib_rs),
+    ];
+    results.messages = vec![(LintSource::new("todo", lib_rs), message)];
+    results.skipped = vec![(LintSource::new("todo", image), SkipReason::BinaryFile)];
+    results
+}
+
+/// A content linter that reports a message for every `TODO` in a file, with a fix that deletes it.
+#[derive(Debug)]
+pub(crate) struct TodoLinter {
+    name: &'static str,
+    default_level: LintLevel,
+    level: Option<LintLevel>,
+    fingerprint: Option<&'static str>,
+    runs: AtomicUsize,
+}
+
+impl TodoLinter {
+    pub(crate) fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            default_level: LintLevel::Error,
+            level: None,
+            fingerprint: None,
+            runs: AtomicUsize::new(0),
+        }
+    }
+
+    /// Sets the default level of this linter.
+    pub(crate) fn with_default_level(mut self, default_level: LintLevel) -> Self {
+        self.default_level = default_level;
+        self
+    }
+
+    /// Writes messages at the given level, rather than at the default level of this linter.
+    pub(crate) fn with_level(mut self, level: LintLevel) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// Allows this linter's results to be cached, with the given fingerprint.
+    pub(crate) fn with_fingerprint(mut self, fingerprint: &'static str) -> Self {
+        self.fingerprint = Some(fingerprint);
+        self
+    }
+
+    /// Returns the number of times this linter has been run against a file.
+    pub(crate) fn runs(&self) -> usize {
+        self.runs.load(Ordering::SeqCst)
+    }
+}
+
+impl Linter for TodoLinter {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn default_level(&self) -> LintLevel {
+        self.default_level
+    }
+}
+
+impl ContentLinter for TodoLinter {
+    fn cache_fingerprint(&self, _ctx: &ContentContext<'_>) -> Option<String> {
+        self.fingerprint.map(str::to_owned)
+    }
+
+    fn run<'l>(
+        &self,
+        ctx: &ContentContext<'l>,
+        out: &mut LintFormatter<'l, '_>,
+    ) -> Result<RunStatus<'l>> {
+        self.runs.fetch_add(1, Ordering::SeqCst);
+        let content = match ctx.content() {
+            Some(content) => content,
+            None => return Ok(RunStatus::Skipped(SkipReason::NonUtf8Content)),
+        };
+
+        let path = ctx.file_ctx().file_path();
+        for (start, todo) in content.match_indices("TODO") {
+            let range = start..(start + todo.len());
+            let fix = LintFix::new("remove TODO", vec![TextEdit::delete(path, range.clone())]);
+            let message = match self.level {
+                Some(level) => LintMessage::new(level, "found TODO"),
+                None => LintMessage::at_default_level("found TODO"),
+            };
+            out.write_message(
+                message
+                    .with_span(LintSpan::from_byte_range(content, range))
+                    .with_fix(fix),
+            );
+        }
+        Ok(RunStatus::Executed)
+    }
+}
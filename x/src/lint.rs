@@ -1,60 +1,362 @@
 // Copyright (c) The nextest Contributors
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use nexlint::{prelude::*, NexLintContext};
+use anyhow::bail;
+use log::{info, warn};
+use nexlint::{
+    prelude::*,
+    report::timings::{write_chrome_trace, write_timings},
+    NexLintContext, DEFAULT_CACHE_DIR,
+};
 use nexlint_lints::{
-    content::*,
-    file_path::*,
-    handle_lint_results,
-    package::*,
-    project::{DirectDepDups, DirectDepDupsConfig},
+    builtin_registry, handle_lint_results, BuiltinLints, ColorMode, FailOn, MessageFormat,
+    ReportOptions, ShowSkipped, TimingsFormat,
 };
+use std::{fs, io};
 use structopt::StructOpt;
 
-static LICENSE_HEADER: &str = "\
-                               SPDX-License-Identifier: MIT OR Apache-2.0\n\
-                               ";
+/// Where `--timings=chrome-trace` writes the trace, relative to the project root.
+const TIMINGS_TRACE_PATH: &str = "target/nexlint-timings.json";
 
 #[derive(Debug, StructOpt)]
 pub struct Args {
+    /// Only lint files under these paths, and the packages containing them
+    #[structopt(value_name = "PATH")]
+    paths: Vec<String>,
+    /// Only lint this workspace package and the files in it (can be repeated)
+    #[structopt(
+        long = "package",
+        short = "p",
+        value_name = "NAME",
+        number_of_values = 1
+    )]
+    packages: Vec<String>,
+    /// List the built-in lints, then exit
+    #[structopt(long, conflicts_with = "explain")]
+    list: bool,
+    /// Explain what a built-in lint checks for and why, then exit
+    #[structopt(long, value_name = "LINT")]
+    explain: Option<String>,
+    /// Lint the whole project, even if default-scope is set to current-dir
+    #[structopt(long, conflicts_with = "paths")]
+    all: bool,
+    /// Stop at the first message that fails the run, as set by --fail-on
     #[structopt(long)]
     fail_fast: bool,
+    /// Only run linters with names matching this glob (can be repeated)
+    #[structopt(long = "lint", value_name = "NAME", number_of_values = 1)]
+    lint: Vec<String>,
+    /// Don't run linters with names matching this glob (can be repeated)
+    #[structopt(long = "skip-lint", value_name = "NAME", number_of_values = 1)]
+    skip_lint: Vec<String>,
+    /// Number of threads to run linters on (0 = number of CPUs)
+    #[structopt(long, short = "j", default_value = "0")]
+    jobs: usize,
+    /// Only lint files changed and packages affected since the merge base with this revision. To
+    /// find affected packages, the merge base is checked out into a Git worktree (registered with
+    /// git worktree add) at target/nexlint-scratch/tree
+    #[structopt(long, value_name = "REV")]
+    changed_since: Option<String>,
+    /// Reuse content lint results for unchanged files, cached under target/nexlint-cache
+    #[structopt(long)]
+    cache: bool,
+    /// Apply suggested fixes, then report any remaining issues
+    #[structopt(long)]
+    fix: bool,
+    /// Only fail on messages that aren't in this baseline file (relative to the project root)
+    #[structopt(long, value_name = "PATH")]
+    baseline: Option<String>,
+    /// Write all current messages to the baseline file instead of reporting them. Can only be used
+    /// when linting the whole project
+    #[structopt(long, requires = "baseline")]
+    write_baseline: bool,
+    /// Drop messages from this lint
+    #[structopt(long = "allow", short = "A", value_name = "LINT", number_of_values = 1)]
+    allow: Vec<String>,
+    /// Report errors from this lint as warnings
+    #[structopt(long = "warn", short = "W", value_name = "LINT", number_of_values = 1)]
+    warn: Vec<String>,
+    /// Report warnings from this lint as errors
+    #[structopt(long = "deny", short = "D", value_name = "LINT", number_of_values = 1)]
+    deny: Vec<String>,
+    /// Lowest message level that causes a failure. Exits with 1 on errors and 2 on warnings
+    #[structopt(
+        long,
+        default_value = "error",
+        possible_values = FailOn::VARIANTS,
+        value_name = "LEVEL"
+    )]
+    fail_on: FailOn,
+    /// Show lints that were skipped: a summary by linter and reason, or every skipped lint
+    #[structopt(
+        long,
+        value_name = "MODE",
+        possible_values = &["summary", "full"]
+    )]
+    show_skipped: Option<Option<ShowSkipped>>,
+    /// Show how long each linter took: as a table on stderr, or as a Chrome trace written to
+    /// target/nexlint-timings.json
+    #[structopt(
+        long,
+        value_name = "FMT",
+        possible_values = TimingsFormat::VARIANTS
+    )]
+    timings: Option<Option<TimingsFormat>>,
+    /// Output format for lint results (defaults to github in GitHub Actions, and human otherwise)
+    #[structopt(long, possible_values = MessageFormat::VARIANTS, value_name = "FMT")]
+    message_format: Option<MessageFormat>,
+    /// When to use colors in human-readable output
+    #[structopt(
+        long,
+        default_value = "auto",
+        possible_values = ColorMode::VARIANTS,
+        value_name = "WHEN"
+    )]
+    color: ColorMode,
+    /// Width to wrap human-readable output to (defaults to $COLUMNS, or 100)
+    #[structopt(long, value_name = "COLUMNS")]
+    width: Option<usize>,
 }
 
 pub fn run(args: Args) -> crate::Result<()> {
-    let direct_dups_config = DirectDepDupsConfig { allow: vec![] };
-    let project_linters: &[&dyn ProjectLinter] = &[&DirectDepDups::new(&direct_dups_config)];
-
-    let package_linters: &[&dyn PackageLinter] = &[
-        &CrateNamesPaths,
-        &IrrelevantBuildDeps,
-        &UnpublishedPackagesOnlyUsePathDependencies::new(),
-        &PublishedPackagesDontDependOnUnpublishedPackages,
-        &OnlyPublishToCratesIo,
-        //&CratesInCratesDirectory,
-    ];
-
-    let file_path_linters: &[&dyn FilePathLinter] =
-        &[&AllowedPaths::new(DEFAULT_ALLOWED_PATHS_REGEX)?];
-
-    let whitespace_exceptions = build_exceptions(&[])?;
-    let content_linters: &[&dyn ContentLinter] = &[
-        &LicenseHeader::new(LICENSE_HEADER),
-        &RootToml,
-        &EofNewline::new(&whitespace_exceptions),
-        &TrailingWhitespace::new(&whitespace_exceptions),
-    ];
-
     let nexlint_context = NexLintContext::from_current_dir()?;
+    if args.list {
+        list_lints(nexlint_context.config());
+        return Ok(());
+    }
+    if let Some(name) = &args.explain {
+        return explain_lint(nexlint_context.config(), name);
+    }
+
+    let builtin_lints = BuiltinLints::from_config(nexlint_context.config())?;
+    let linters = builtin_lints.linters();
+    let project_linters = linters.project_linters();
+    let package_linters = linters.package_linters();
+    let file_path_linters = linters.file_path_linters();
+    let content_linters = linters.content_linters();
+
+    // Later flags on the command line should take precedence, but structopt doesn't preserve the
+    // relative order of different flags. Apply them from least to most severe instead.
+    let level_overrides: Vec<_> = args
+        .allow
+        .iter()
+        .map(|lint| (lint, OverrideLevel::Allow))
+        .chain(args.warn.iter().map(|lint| (lint, OverrideLevel::Warn)))
+        .chain(args.deny.iter().map(|lint| (lint, OverrideLevel::Deny)))
+        .map(|(lint, level)| LevelOverride::new(lint, level))
+        .collect();
+
+    let mut paths = args
+        .paths
+        .iter()
+        .map(|path| nexlint_context.project_rel_path(path))
+        .collect::<Result<Vec<_>>>()?;
+    if paths.is_empty()
+        && args.packages.is_empty()
+        && !args.all
+        && nexlint_context.config().default_scope() == DefaultScope::CurrentDir
+        && !nexlint_context.current_dir_is_root()
+    {
+        info!(
+            "linting {} (pass --all to lint the whole project)",
+            nexlint_context.current_rel_dir()
+        );
+        paths.push(nexlint_context.current_rel_dir().to_owned());
+    }
+
+    // A run that doesn't lint the whole project can't tell whether baseline entries for what it
+    // didn't lint are stale, and writing out its messages would drop those entries.
+    let restricted = !paths.is_empty()
+        || !args.packages.is_empty()
+        || !args.lint.is_empty()
+        || !args.skip_lint.is_empty()
+        || args.changed_since.is_some()
+        || args.fail_fast;
+    if restricted && args.write_baseline {
+        bail!(
+            "--write-baseline needs to lint the whole project, so it can't be used with paths, \
+             --package, --lint, --skip-lint, --changed-since or --fail-fast \
+             (pass --all if default-scope is set to current-dir)"
+        );
+    }
+
+    let selection = LintSelection::new()
+        .with_lints(args.lint.iter().map(String::as_str))?
+        .with_skip_lints(args.skip_lint.iter().map(String::as_str))?;
+    let cache_dir = nexlint_context.project_root().join(DEFAULT_CACHE_DIR);
+
+    // With --fail-on never, no message fails the run, so there's nothing to stop at.
+    let fail_level = args.fail_on.level();
     let engine = LintEngineConfig::new(&nexlint_context)
-        .with_project_linters(project_linters)
-        .with_package_linters(package_linters)
-        .with_file_path_linters(file_path_linters)
-        .with_content_linters(content_linters)
-        .fail_fast(args.fail_fast)
+        .with_project_linters(&project_linters)
+        .with_package_linters(&package_linters)
+        .with_file_path_linters(&file_path_linters)
+        .with_content_linters(&content_linters)
+        .with_level_overrides(&level_overrides)
+        .with_selection(&selection)
+        .fail_fast(args.fail_fast && fail_level.is_some())
+        .fail_fast_level(fail_level.unwrap_or(LintLevel::Error))
+        .threads(args.jobs)
+        .changed_since(args.changed_since.as_deref())
+        .with_paths(&paths)
+        .with_packages(&args.packages)
+        .with_cache_dir(args.cache.then_some(cache_dir.as_path()))
         .build();
 
-    let results = engine.run()?;
+    let mut results = if args.fix {
+        let fix_results = engine.fix()?;
+        info!("applied {} fixes", fix_results.applied);
+        if !fix_results.converged {
+            warn!("fixes did not converge: some issues may have been partially fixed");
+        }
+        fix_results.results
+    } else {
+        engine.run()?
+    };
+
+    match args.timings {
+        None => {}
+        Some(None | Some(TimingsFormat::Table)) => write_timings(&results, io::stderr().lock())?,
+        Some(Some(TimingsFormat::ChromeTrace)) => {
+            let trace_path = nexlint_context.project_root().join(TIMINGS_TRACE_PATH);
+            if let Some(parent) = trace_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_chrome_trace(&results, fs::File::create(&trace_path)?)?;
+            info!("wrote lint timings to {}", trace_path);
+        }
+    }
+
+    if let Some(baseline) = &args.baseline {
+        let baseline_path = nexlint_context.project_root().join(baseline);
+        if args.write_baseline {
+            let baseline = Baseline::from_results(&results);
+            baseline.write(&baseline_path)?;
+            info!(
+                "wrote {} messages to baseline {}",
+                baseline.len(),
+                baseline_path
+            );
+            return Ok(());
+        }
+
+        let stale = Baseline::load(&baseline_path)?.apply(&mut results);
+        if !restricted {
+            for entry in &stale {
+                warn!(
+                    "stale baseline entry: [{}] [{}]: {} ({} fewer occurrences)",
+                    entry.linter, entry.kind, entry.message, entry.count
+                );
+            }
+            if !stale.is_empty() {
+                warn!("run with --write-baseline to remove stale baseline entries");
+            }
+        }
+    }
+
+    if !args.fix {
+        let fixable = results
+            .messages
+            .iter()
+            .filter(|(_, message)| message.fix().is_some())
+            .count();
+        if fixable > 0 {
+            info!("{} issues can be fixed automatically with --fix", fixable);
+        }
+    }
+
+    let report_options = ReportOptions {
+        message_format: args.message_format.unwrap_or_else(MessageFormat::from_env),
+        fail_on: args.fail_on,
+        show_skipped: match args.show_skipped {
+            None => ShowSkipped::Never,
+            Some(mode) => mode.unwrap_or(ShowSkipped::Summary),
+        },
+        color: args.color,
+        width: args.width,
+    };
+    handle_lint_results(results, &report_options)
+}
+
+/// Prints a table of the built-in lints.
+fn list_lints(config: &NexLintConfig) {
+    let registry = builtin_registry();
+    let name_width = registry
+        .iter()
+        .map(|lint| lint.info().name().len())
+        .max()
+        .unwrap_or_default();
+
+    println!(
+        "{:name_width$}  {:9}  {:12}  {:7}  {:8}  DESCRIPTION",
+        "NAME", "KIND", "CATEGORY", "LEVEL", "ENABLED",
+    );
+    for lint in &registry {
+        let info = lint.info();
+        println!(
+            "{:name_width$}  {:9}  {:12}  {:7}  {:8}  {}",
+            info.name(),
+            info.kind(),
+            info.category(),
+            info.default_level().to_string().to_lowercase(),
+            if lint.is_enabled(config) { "yes" } else { "no" },
+            info.description(),
+        );
+    }
+}
+
+/// Prints the explanation for a built-in lint, followed by its metadata.
+fn explain_lint(config: &NexLintConfig, name: &str) -> crate::Result<()> {
+    let registry = builtin_registry();
+    let lint = match registry.iter().find(|lint| lint.info().name() == name) {
+        Some(lint) => lint,
+        None => {
+            let mut known: Vec<_> = registry.iter().map(|lint| lint.info().name()).collect();
+            known.sort_unstable();
+            return Err(SystemError::UnknownLint {
+                pattern: name.to_owned(),
+                known,
+            }
+            .into());
+        }
+    };
+
+    let info = lint.info();
+    println!("{}", info.explanation().trim_end());
+    println!();
+    println!("Kind: {} lint", info.kind());
+    println!("Category: {}", info.category());
+    println!(
+        "Default level: {}",
+        info.default_level().to_string().to_lowercase()
+    );
+    println!(
+        "Enabled: {} ({} by default)",
+        if lint.is_enabled(config) { "yes" } else { "no" },
+        if lint.enabled_by_default() {
+            "enabled"
+        } else {
+            "disabled"
+        },
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, structopt::clap::Error> {
+        Args::from_iter_safe(std::iter::once("lint").chain(args.iter().copied()))
+    }
 
-    handle_lint_results(results)
+    #[test]
+    fn jobs() {
+        // By default, linters run on one thread per CPU.
+        assert_eq!(parse(&[]).expect("parsed").jobs, 0);
+        assert_eq!(parse(&["-j", "1"]).expect("parsed").jobs, 1);
+        assert_eq!(parse(&["--jobs", "4"]).expect("parsed").jobs, 4);
+        parse(&["-j", "-1"]).expect_err("negative job counts are rejected");
+        parse(&["-j", "many"]).expect_err("non-numeric job counts are rejected");
+    }
 }
@@ -6,7 +6,8 @@
 use chrono::Local;
 use env_logger::{self, fmt::Color};
 use log::Level;
-use std::io::Write;
+use nexlint_lints::LintFailure;
+use std::{io::Write, process};
 use structopt::StructOpt;
 
 mod lint;
@@ -21,6 +22,8 @@ struct Args {
 }
 
 #[derive(Debug, StructOpt)]
+// Commands are parsed once at startup, so the size of the largest one doesn't matter.
+#[allow(clippy::large_enum_variant)]
 enum Command {
     #[structopt(name = "lint")]
     /// Run lints
@@ -54,8 +57,17 @@ fn main() -> Result<()> {
 
     let args = Args::from_args();
 
-    match args.cmd {
+    let result = match args.cmd {
         Command::Lint(args) => lint::run(args),
         Command::Playground(args) => playground::run(args),
+    };
+
+    // Lint failures have their own exit codes, so that warnings can be told apart from errors.
+    if let Err(err) = &result {
+        if let Some(failure) = err.downcast_ref::<LintFailure>() {
+            eprintln!("Error: {}", failure);
+            process::exit(failure.exit_code());
+        }
     }
+    result
 }
@@ -11,7 +11,7 @@
 #![allow(unused_variables)]
 
 use nexlint::{prelude::*, NexLintContext};
-use nexlint_lints::handle_lint_results;
+use nexlint_lints::{handle_lint_results, ReportOptions};
 use structopt::StructOpt;
 
 #[derive(Copy, Clone, Debug)]
@@ -119,5 +119,5 @@ pub fn run(args: Args) -> crate::Result<()> {
 
     let results = engine.run()?;
 
-    handle_lint_results(results)
+    handle_lint_results(results, &ReportOptions::default())
 }